@@ -0,0 +1,53 @@
+//! Rough timings of the batched shuffles against a scalar loop, and of the cached sip seed state
+//! against a round function without it, run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use blackrock2::generator::{BlackRockGenerator, RoundFunction, SipRound};
+
+// `SipRound` without the seed state the generator caches for it
+struct Uncached;
+
+impl RoundFunction for Uncached {
+    fn mix(&self, j: u64, right: u64, seed: u64) -> u64 {
+        SipRound.mix(j, right, seed)
+    }
+}
+
+fn time(name: &str, mut f: impl FnMut()) {
+    f();
+
+    let mut runs = 0;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(1) {
+        f();
+        runs += 1;
+    }
+    println!("{name:<24} {:?}/iter", start.elapsed() / runs);
+}
+
+fn main() {
+    let randomizer = BlackRockGenerator::with_seed(1 << 32, 6);
+    let input = (0..1 << 16).collect::<Vec<u64>>();
+    let mut out = vec![0; input.len()];
+
+    time("shuffle", || {
+        for (&m, c) in input.iter().zip(&mut out) {
+            *c = randomizer.shuffle(black_box(m));
+        }
+        black_box(&out);
+    });
+
+    time("shuffle_into", || {
+        randomizer.shuffle_into(black_box(&input), &mut out);
+        black_box(&out);
+    });
+
+    let uncached = BlackRockGenerator::with_round_function(1 << 32, 6, 3, Uncached);
+    time("shuffle uncached", || {
+        for (&m, c) in input.iter().zip(&mut out) {
+            *c = uncached.shuffle(black_box(m));
+        }
+        black_box(&out);
+    });
+}
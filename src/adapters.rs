@@ -0,0 +1,318 @@
+//! Iterator adapters returned by [`BlackRockIter`] and [`BlackRockIpGenerator`](crate::BlackRockIpGenerator) methods.
+
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::net::Ipv4Addr;
+use core::ops::Range;
+use crate::BlackRockIter;
+use crate::generator::BlackRockGenerator;
+
+#[inline]
+fn sum_hints(a: (usize, Option<usize>), b: (usize, Option<usize>)) -> (usize, Option<usize>) {
+    (a.0.saturating_add(b.0), a.1.zip(b.1).and_then(|(a, b)| a.checked_add(b)))
+}
+
+/// Created by [`BlackRockIter::with_priority`].
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Prioritized<F> {
+    pub(crate) iter: BlackRockIter,
+    pub(crate) rest: Range<u64>,
+    pub(crate) is_priority: F,
+}
+
+impl<F: Fn(u64) -> bool> Iterator for Prioritized<F> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (generator, offset) = (&self.iter.generator, self.iter.offset);
+        let is_priority = &self.is_priority;
+
+        self.iter.range.by_ref()
+            .map(|x| generator.shuffle(x) + offset)
+            .find(|&x| is_priority(x))
+            .or_else(|| {
+                self.rest.by_ref()
+                    .map(|x| generator.shuffle(x) + offset)
+                    .find(|&x| !is_priority(x))
+            })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, sum_hints(self.iter.range.size_hint(), self.rest.size_hint()).1)
+    }
+}
+
+impl<F: Fn(u64) -> bool> FusedIterator for Prioritized<F> {}
+
+/// Created by [`BlackRockIter::with_rotation`].
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Rotating {
+    pub(crate) range: Range<u64>,
+    pub(crate) generator: BlackRockGenerator,
+    pub(crate) base_seed: u64,
+    pub(crate) rounds: usize,
+    pub(crate) every: u64,
+    pub(crate) emitted: u64,
+}
+
+impl Rotating {
+    /// The seed the next value is shuffled with.
+    pub fn current_seed(&self) -> u64 {
+        self.base_seed.wrapping_add(self.emitted / self.every)
+    }
+}
+
+impl Iterator for Rotating {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.range.next()?;
+        if self.emitted != 0 && self.emitted % self.every == 0 {
+            self.generator = BlackRockGenerator::with_seed_and_rounds(self.range.end, self.current_seed(), self.rounds);
+        }
+
+        self.emitted += 1;
+        Some(self.generator.shuffle(pos))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl FusedIterator for Rotating {}
+
+/// Created by [`BlackRockIter::over_complement`].
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Complement {
+    pub(crate) iter: BlackRockIter,
+    // per merged hole, the values kept before it and the values skipped up to its end
+    pub(crate) holes: Vec<(u64, u64)>,
+}
+
+impl Complement {
+    #[inline]
+    fn value(&self, rank: u64) -> u64 {
+        let passed = self.holes.partition_point(|&(kept_before, _)| kept_before <= rank);
+        rank + passed.checked_sub(1).map_or(0, |hole| self.holes[hole].1)
+    }
+}
+
+impl Iterator for Complement {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|rank| self.value(rank))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n).map(|rank| self.value(rank))
+    }
+}
+
+impl DoubleEndedIterator for Complement {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|rank| self.value(rank))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth_back(n).map(|rank| self.value(rank))
+    }
+}
+
+// any `u64` range fits in a `usize` on 64 bit targets, elsewhere the size hint is the best there is
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for Complement {
+    fn len(&self) -> usize {
+        (self.iter.range.end - self.iter.range.start) as usize
+    }
+}
+
+impl FusedIterator for Complement {}
+
+/// Created by [`BlackRockIter::lanes`].
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Lanes<F> {
+    pub(crate) iter: BlackRockIter,
+    pub(crate) lane_of: F,
+    pub(crate) buffers: Vec<VecDeque<u64>>,
+    pub(crate) next_lane: usize,
+}
+
+impl<F: Fn(u64) -> usize> Iterator for Lanes<F> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let lanes = self.buffers.len();
+
+        for _ in 0..lanes {
+            let lane = self.next_lane;
+            self.next_lane = (lane + 1) % lanes;
+
+            if let Some(x) = self.buffers[lane].pop_front() {
+                return Some(x);
+            }
+
+            for x in self.iter.by_ref() {
+                let target = (self.lane_of)(x);
+                assert!(target < lanes, "lane {target} is out of bounds for {lanes} lanes");
+
+                if target == lane {
+                    return Some(x);
+                }
+                self.buffers[target].push_back(x);
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.buffers.iter().map(VecDeque::len).sum();
+        sum_hints(self.iter.size_hint(), (buffered, Some(buffered)))
+    }
+}
+
+impl<F: Fn(u64) -> usize> FusedIterator for Lanes<F> {}
+
+/// Created by [`BlackRockIpGenerator::batch_by_prefix`](crate::BlackRockIpGenerator::batch_by_prefix).
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct PrefixBatches<I> {
+    pub(crate) iter: I,
+    pub(crate) prefix: u8,
+    pub(crate) lookahead: VecDeque<Ipv4Addr>,
+    pub(crate) max_lookahead: usize,
+}
+
+#[inline]
+pub(crate) fn network(addr: Ipv4Addr, prefix: u8) -> u32 {
+    addr.to_bits().checked_shr(32 - u32::from(prefix)).unwrap_or(0)
+}
+
+impl<I: Iterator<Item = Ipv4Addr>> Iterator for PrefixBatches<I> {
+    type Item = Vec<Ipv4Addr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.lookahead.pop_front().or_else(|| self.iter.next())?;
+
+        let missing = self.max_lookahead - self.lookahead.len();
+        self.lookahead.extend(self.iter.by_ref().take(missing));
+
+        let prefix = self.prefix;
+        let mut batch = vec![first];
+        self.lookahead.retain(|&addr| {
+            let same = network(addr, prefix) == network(first, prefix);
+            if same {
+                batch.push(addr);
+            }
+            !same
+        });
+
+        Some(batch)
+    }
+}
+
+impl<I: FusedIterator<Item = Ipv4Addr>> FusedIterator for PrefixBatches<I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn priority_first() {
+        let range = 1000;
+        let is_priority = |x: u64| x % 7 == 0;
+
+        let values = BlackRockIter::with_seed(range, 1).with_priority(is_priority).collect::<Vec<_>>();
+        let split = values.iter().position(|&x| !is_priority(x)).unwrap();
+        assert_eq!(split as u64, range.div_ceil(7));
+        assert!(values[split..].iter().all(|&x| !is_priority(x)));
+
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..range).collect::<Vec<_>>());
+
+        // each tier keeps the underlying shuffled order
+        let base = BlackRockIter::with_seed(range, 1).collect::<Vec<_>>();
+        let tier = base.iter().copied().filter(|&x| is_priority(x)).collect::<Vec<_>>();
+        assert_eq!(values[..split], tier);
+    }
+
+    #[test]
+    fn lanes() {
+        let values = BlackRockIter::with_seed(999, 2).lanes(3, |x| (x % 3) as usize).collect::<Vec<_>>();
+        assert!(values.iter().enumerate().all(|(i, &x)| x % 3 == i as u64 % 3));
+
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..999).collect::<Vec<_>>());
+
+        // lane 0 runs dry after 250 values, the rest is all lane 1
+        let lane_of = |x: u64| usize::from(x % 4 != 0);
+        let values = BlackRockIter::with_seed(1000, 2).lanes(2, lane_of).collect::<Vec<_>>();
+        assert_eq!(values.len(), 1000);
+        assert!(values[..500].iter().enumerate().all(|(i, &x)| lane_of(x) == i % 2));
+        assert!(values[500..].iter().all(|&x| lane_of(x) == 1));
+
+        let base = BlackRockIter::with_seed(1000, 2).filter(|&x| lane_of(x) == 1);
+        assert!(values.iter().copied().filter(|&x| lane_of(x) == 1).eq(base));
+    }
+
+    #[test]
+    fn prefix_batches() {
+        let addrs = || BlackRockIter::with_seed(1 << 16, 8).map(|x| Ipv4Addr::from_bits(x as u32));
+        let batches = PrefixBatches {
+            iter: addrs(),
+            prefix: 24,
+            lookahead: VecDeque::new(),
+            max_lookahead: 64,
+        }.collect::<Vec<_>>();
+
+        assert!(batches.iter().all(|batch| batch.iter().all(|ip| ip.octets()[..3] == batch[0].octets()[..3])));
+        assert!(batches.iter().any(|batch| batch.len() > 1));
+        assert!(batches.len() < 1 << 16);
+
+        let mut flat = batches.concat();
+        assert_eq!(flat.len(), 1 << 16);
+        flat.sort_unstable();
+        assert!(flat.into_iter().eq((0..1 << 16).map(Ipv4Addr::from_bits)));
+
+        // without lookahead nothing gets grouped
+        let single = PrefixBatches {
+            iter: addrs(),
+            prefix: 24,
+            lookahead: VecDeque::new(),
+            max_lookahead: 0,
+        };
+        assert!(single.map(|batch| batch[..] == [batch[0]]).eq(addrs().map(|_| true)));
+    }
+
+    #[test]
+    fn rotation() {
+        let (range, base_seed, every) = (1000, 40, 128);
+        let values = BlackRockIter::with_rotation(range, base_seed, 3, every).collect::<Vec<_>>();
+        assert_eq!(values.len() as u64, range);
+        assert_eq!(values, BlackRockIter::with_rotation(range, base_seed, 3, every).collect::<Vec<_>>());
+
+        for (pos, &x) in (0..).zip(&values) {
+            let generator = BlackRockGenerator::with_seed_and_rounds(range, base_seed + pos / every, 3);
+            assert_eq!(generator.shuffle(pos), x, "position {pos}");
+        }
+
+        let mut iter = BlackRockIter::with_rotation(range, base_seed, 3, every);
+        assert_eq!(iter.current_seed(), base_seed);
+        iter.by_ref().take(every as usize).for_each(drop);
+        assert_eq!(iter.current_seed(), base_seed + 1);
+    }
+}
@@ -0,0 +1,122 @@
+//! A Bloom filter for skipping recently scanned targets, behind the `bloom` feature.
+
+use alloc::vec::Vec;
+use crate::generator::sip;
+
+// fixed key, a filter has to hash the same way on insert and lookup.
+const KEY: u64 = 0x2545f4914f6cdd1d;
+
+/// A fixed-size Bloom filter over `u64` values.
+///
+/// Lookups can return false positives but never false negatives,
+/// see [`BlackRockIter::skip_bloom`](crate::BlackRockIter::skip_bloom) for what that means when skipping targets.
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Create an empty filter with `bits` bits (rounded up to a multiple of 64) and `hashes` hash functions.
+    ///
+    /// # Panics
+    /// if `bits` or `hashes` is zero.
+    pub fn new(bits: usize, hashes: u32) -> Self {
+        assert!(bits > 0, "a bloom filter needs at least one bit");
+        assert!(hashes > 0, "a bloom filter needs at least one hash function");
+
+        Self {
+            bits: vec![0; bits.div_ceil(64)],
+            hashes,
+        }
+    }
+
+    /// Create an empty filter sized to hold `items` values with roughly
+    /// a `false_positive_rate` chance of a false positive, using the usual
+    /// `m = -n ln(p) / ln(2)²` and `k = m / n ln(2)` sizing.
+    ///
+    /// # Panics
+    /// if `false_positive_rate` is not in `(0, 1)`, or the filter would need more than `usize::MAX` bits.
+    pub fn with_false_positive_rate(items: usize, false_positive_rate: f64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false positive rate must be in (0, 1), got {false_positive_rate}"
+        );
+
+        let ln2 = std::f64::consts::LN_2;
+        let n = items.max(1) as f64;
+        let bits = (-n * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        // `usize::MAX as f64` rounds up to the next power of two, which doesn't fit either
+        assert!(
+            bits < usize::MAX as f64,
+            "{items} items at a false positive rate of {false_positive_rate} need {bits} bits, more than fit in a usize"
+        );
+        let hashes = (bits / n * ln2).round();
+
+        Self::new(bits as usize, (hashes as u32).max(1))
+    }
+
+    // double hashing, h1 + i * h2
+    #[inline]
+    fn indices(&self, x: u64) -> impl Iterator<Item = u64> {
+        let h1 = sip(0, x, KEY);
+        let h2 = sip(1, x, KEY) | 1;
+        let len = self.bits.len() as u64 * 64;
+        (0..self.hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % len)
+    }
+
+    /// Add `x` to the filter.
+    pub fn insert(&mut self, x: u64) {
+        let indices = self.indices(x);
+        let bits = &mut self.bits;
+        for i in indices {
+            bits[(i / 64) as usize] |= 1 << (i % 64);
+        }
+    }
+
+    /// Returns `true` if `x` may have been inserted, `false` if it definitely was not.
+    pub fn contains(&self, x: u64) -> bool {
+        self.indices(x).all(|i| self.bits[(i / 64) as usize] & (1 << (i % 64)) != 0)
+    }
+}
+
+impl Extend<u64> for BloomFilter {
+    fn extend<T: IntoIterator<Item = u64>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|x| self.insert(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlackRockIter;
+
+    #[test]
+    fn no_false_negatives() {
+        let mut filter = BloomFilter::with_false_positive_rate(1000, 0.01);
+        filter.extend((0..10_000).step_by(10));
+
+        assert!((0..10_000).step_by(10).all(|x| filter.contains(x)));
+    }
+
+    #[test]
+    fn skip_bloom() {
+        let range = 10_000;
+        let mut filter = BloomFilter::with_false_positive_rate(1000, 0.01);
+        filter.extend((0..range).step_by(10));
+
+        let emitted = BlackRockIter::with_seed(range, 5).skip_bloom(&filter).collect::<Vec<_>>();
+        assert!(emitted.iter().all(|x| x % 10 != 0));
+
+        // everything not inserted and not emitted was a false skip
+        let false_skips = range - range / 10 - emitted.len() as u64;
+        let rate = false_skips as f64 / (range - range / 10) as f64;
+        assert!(rate < 0.03, "false skip rate {rate}");
+    }
+
+    #[test]
+    #[should_panic = "more than fit in a usize"]
+    fn too_many_bits() {
+        let _ = BloomFilter::with_false_positive_rate(usize::MAX, 1e-300);
+    }
+}
@@ -0,0 +1,136 @@
+//! A builder for [`BlackRockGenerator`], [`BlackRockIter`] and [`BlackRockIpGenerator`],
+//! for when the `with_*` constructors don't cover the combination of options needed.
+
+use crate::generator::BlackRockGenerator;
+use crate::{BlackRockIpGenerator, BlackRockIter};
+
+/// Collects the range, seed and rounds of a generator, then builds it.
+///
+/// Unset options default to an empty range, a random seed and 3 rounds.
+///
+/// ```
+/// # use blackrock2::builder::BlackRockBuilder;
+/// let ips = BlackRockBuilder::new().ipv4().seed(0x5eed).rounds(4).build_ip();
+/// let ports = BlackRockBuilder::new().range(65536).seed(0x5eed).build_iter();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use = "builders do nothing unless built"]
+pub struct BlackRockBuilder {
+    range: u64,
+    seed: Option<u64>,
+    rounds: usize,
+}
+
+impl Default for BlackRockBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlackRockBuilder {
+    /// A builder with every option unset.
+    pub const fn new() -> Self {
+        Self { range: 0, seed: None, rounds: 3 }
+    }
+
+    /// Shuffle `0..range`.
+    pub const fn range(mut self, range: u64) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Shuffle the whole IPv4 space, `0..2^32`.
+    pub const fn ipv4(self) -> Self {
+        self.range(1 << 32)
+    }
+
+    /// Use a fixed seed instead of a random one.
+    pub const fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The amount of Feistel rounds, see [`BlackRockGenerator::with_seed_and_rounds`].
+    pub const fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    #[track_caller]
+    fn resolved_seed(&self) -> u64 {
+        match self.seed {
+            Some(seed) => seed,
+            #[cfg(feature = "rand")]
+            None => rand::random(),
+            #[cfg(not(feature = "rand"))]
+            None => panic!("need a seed without the `rand` feature"),
+        }
+    }
+
+    /// Build a [`BlackRockGenerator`].
+    ///
+    /// # Panics
+    /// if no seed was set and the `rand` feature is disabled.
+    #[track_caller]
+    pub fn build(&self) -> BlackRockGenerator {
+        BlackRockGenerator::with_seed_and_rounds(self.range, self.resolved_seed(), self.rounds)
+    }
+
+    /// Build a [`BlackRockIter`] over the shuffled range.
+    ///
+    /// # Panics
+    /// if no seed was set and the `rand` feature is disabled.
+    #[track_caller]
+    pub fn build_iter(&self) -> BlackRockIter {
+        BlackRockIter::with_seed_and_rounds(self.range, self.resolved_seed(), self.rounds)
+    }
+
+    /// Build a [`BlackRockIpGenerator`] over the addresses `0..range`, usually after [`ipv4`](Self::ipv4).
+    ///
+    /// # Panics
+    /// if the range is larger than the IPv4 space, or no seed was set and the `rand` feature is disabled.
+    #[track_caller]
+    pub fn build_ip(&self) -> BlackRockIpGenerator {
+        assert!(self.range <= 1 << 32, "range {} is larger than the IPv4 space", self.range);
+        BlackRockIpGenerator(self.build_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_constructors() {
+        for (range, seed, rounds) in [(0, 1, 3), (1, 2, 3), (1000, 3, 4), (65_537, 4, 0), (1 << 40, 5, 7)] {
+            let builder = BlackRockBuilder::new().range(range).seed(seed).rounds(rounds);
+
+            let built = builder.build();
+            let direct = BlackRockGenerator::with_seed_and_rounds(range, seed, rounds);
+            assert_eq!(built.config(), direct.config());
+            assert!((0..range.min(1000)).all(|m| built.shuffle(m) == direct.shuffle(m)));
+
+            assert!(builder.build_iter().take(1000).eq(BlackRockIter::with_seed_and_rounds(range, seed, rounds).take(1000)));
+        }
+
+        let defaults = BlackRockBuilder::new().range(1000).seed(7).build_iter();
+        assert!(defaults.eq(BlackRockIter::with_seed(1000, 7)));
+
+        let ips = BlackRockBuilder::new().ipv4().seed(1).build_ip();
+        assert!(ips.take(1000).eq(BlackRockIpGenerator(BlackRockIter::with_seed(1 << 32, 1)).take(1000)));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_seed() {
+        let built = BlackRockBuilder::new().range(1000).build();
+        assert_eq!(built.config().0, 1000);
+        assert!(built.is_permutation());
+    }
+
+    #[test]
+    #[should_panic = "range 4294967297 is larger than the IPv4 space"]
+    fn ip_range_too_large() {
+        let _ = BlackRockBuilder::new().range((1 << 32) + 1).seed(1).build_ip();
+    }
+}
@@ -0,0 +1,174 @@
+//! Resumable checkpoints of a [`BlackRockIter`].
+
+use crate::BlackRockIter;
+use crate::error::FromBytesError;
+use crate::generator::BlackRockGenerator;
+
+/// A checkpoint of a [`BlackRockIter`], created by [`BlackRockIter::with_cursor`].
+///
+/// Holds the generator config and the remaining positions, enough to rebuild the iterator
+/// on another run or another machine with [`resume`](Self::resume).
+///
+/// With the `serde` feature it is `Serialize` and `Deserialize` too, deserializing validates
+/// the positions like [`from_bytes`](Self::from_bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedCursor"))]
+pub struct Cursor {
+    pub(crate) range: u64,
+    pub(crate) offset: u64,
+    pub(crate) seed: u64,
+    pub(crate) rounds: usize,
+    pub(crate) next: u64,
+    pub(crate) end: u64,
+}
+
+impl Cursor {
+    /// Length of the output of [`to_bytes`](Self::to_bytes).
+    pub const ENCODED_LEN: usize = 1 + 6 * 8 + 8;
+
+    /// The position in the shuffled sequence the resumed iterator starts at.
+    pub const fn position(&self) -> u64 {
+        self.next
+    }
+
+    /// Rebuild the iterator, it continues with the value after the one this cursor was yielded with.
+    pub fn resume(self) -> BlackRockIter {
+        BlackRockIter {
+            range: self.next..self.end,
+            generator: BlackRockGenerator::with_seed_and_rounds(self.range, self.seed, self.rounds),
+            offset: self.offset,
+        }
+    }
+
+    /// Encode this cursor in the same style as [`BlackRockGenerator::to_bytes`]:
+    /// the [`ALGORITHM_VERSION`](BlackRockGenerator::ALGORITHM_VERSION) byte, then `range`, the offset, `seed`, `rounds`,
+    /// the next position and the end position as little endian `u64`s, then a checksum of all of the above.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0; Self::ENCODED_LEN];
+        bytes[0] = BlackRockGenerator::ALGORITHM_VERSION;
+
+        let words = [self.range, self.offset, self.seed, self.rounds as u64, self.next, self.end];
+        for (chunk, word) in bytes[1..].chunks_exact_mut(8).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+
+        let checksum = BlackRockGenerator::checksum(&bytes[..Self::ENCODED_LEN - 8]);
+        bytes[Self::ENCODED_LEN - 8..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Decode a cursor written by [`to_bytes`](Self::to_bytes), validating its version, checksum and positions.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FromBytesError> {
+        let bytes = <&[u8; Self::ENCODED_LEN]>::try_from(bytes)
+            .map_err(|_| FromBytesError::InvalidLength(bytes.len()))?;
+
+        let (header, checksum) = bytes.split_at(Self::ENCODED_LEN - 8);
+        if BlackRockGenerator::checksum(header).to_le_bytes() != checksum {
+            return Err(FromBytesError::ChecksumMismatch);
+        }
+
+        if header[0] != BlackRockGenerator::ALGORITHM_VERSION {
+            return Err(FromBytesError::UnsupportedVersion(header[0]));
+        }
+
+        let word = |i: usize| u64::from_le_bytes(header[1 + i * 8..][..8].try_into().unwrap());
+        let rounds = usize::try_from(word(3)).map_err(|_| FromBytesError::RoundsOverflow)?;
+        let (range, offset, next, end) = (word(0), word(1), word(4), word(5));
+        if !valid_positions(range, offset, next, end) {
+            return Err(FromBytesError::InvalidPosition);
+        }
+
+        Ok(Self { range, offset, seed: word(2), rounds, next, end })
+    }
+}
+
+// `next..end` has to lie within the range, and every value shifted by the offset has to fit in a `u64`
+pub(crate) const fn valid_positions(range: u64, offset: u64, next: u64, end: u64) -> bool {
+    next <= end && end <= range && offset.checked_add(range.saturating_sub(1)).is_some()
+}
+
+// a deserialized `Cursor` before its positions are validated
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Cursor")]
+struct UncheckedCursor {
+    range: u64,
+    offset: u64,
+    seed: u64,
+    rounds: usize,
+    next: u64,
+    end: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedCursor> for Cursor {
+    type Error = FromBytesError;
+
+    fn try_from(UncheckedCursor { range, offset, seed, rounds, next, end }: UncheckedCursor) -> Result<Self, Self::Error> {
+        if !valid_positions(range, offset, next, end) {
+            return Err(FromBytesError::InvalidPosition);
+        }
+
+        Ok(Self { range, offset, seed, rounds, next, end })
+    }
+}
+
+impl From<Cursor> for BlackRockIter {
+    fn from(cursor: Cursor) -> Self {
+        cursor.resume()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume() {
+        let full = BlackRockIter::with_seed(1000, 6).collect::<Vec<_>>();
+
+        let (values, cursors): (Vec<_>, Vec<_>) = BlackRockIter::with_seed(1000, 6).with_cursor().unzip();
+        assert_eq!(values, full);
+        assert!(cursors.iter().enumerate().all(|(i, cursor)| cursor.position() == i as u64 + 1));
+
+        let bytes = cursors[399].to_bytes();
+        let resumed = Cursor::from_bytes(&bytes).unwrap().resume();
+        assert!(resumed.eq(full[400..].iter().copied()));
+
+        assert_eq!(BlackRockIter::from(cursors[999]).next(), None);
+
+        let (_, cursor) = BlackRockIter::inclusive(500..=600, 6, 3).with_cursor().nth(50).unwrap();
+        let resumed = Cursor::from_bytes(&cursor.to_bytes()).unwrap().resume();
+        assert!(resumed.eq(BlackRockIter::inclusive(500..=600, 6, 3).skip(51)));
+    }
+
+    #[test]
+    fn rejects_corruption() {
+        let (_, cursor) = BlackRockIter::with_seed(1000, 6).with_cursor().nth(10).unwrap();
+        let bytes = cursor.to_bytes();
+
+        assert_eq!(Cursor::from_bytes(&bytes[1..]), Err(FromBytesError::InvalidLength(Cursor::ENCODED_LEN - 1)));
+
+        let mut flipped = bytes;
+        flipped[30] ^= 1;
+        assert_eq!(Cursor::from_bytes(&flipped), Err(FromBytesError::ChecksumMismatch));
+
+        let past_end = Cursor { next: 1001, end: 1001, ..cursor };
+        assert_eq!(Cursor::from_bytes(&past_end.to_bytes()), Err(FromBytesError::InvalidPosition));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let (_, cursor) = BlackRockIter::inclusive(500..=600, 6, 3).with_cursor().nth(50).unwrap();
+        let json = serde_json::to_string(&cursor).unwrap();
+        let decoded = serde_json::from_str::<Cursor>(&json).unwrap();
+        assert_eq!(decoded, cursor);
+        assert!(decoded.resume().eq(BlackRockIter::inclusive(500..=600, 6, 3).skip(51)));
+
+        let past_end = serde_json::to_string(&Cursor { next: 1001, end: 1001, ..cursor }).unwrap();
+        let err = serde_json::from_str::<Cursor>(&past_end).err().unwrap();
+        assert!(err.to_string().contains("cursor position is outside of the range"));
+    }
+}
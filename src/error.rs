@@ -0,0 +1,118 @@
+//! Error types returned by this crate.
+
+use core::fmt;
+
+/// Returned by [`BlackRockGenerator::from_bytes`](crate::generator::BlackRockGenerator::from_bytes)
+/// and [`Cursor::from_bytes`](crate::cursor::Cursor::from_bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FromBytesError {
+    /// The input isn't exactly as long as an encoded token, contains the actual length.
+    InvalidLength(usize),
+    /// The input was written by an unknown version of the algorithm.
+    UnsupportedVersion(u8),
+    /// The trailing checksum doesn't match the header.
+    ChecksumMismatch,
+    /// The round count doesn't fit in a `usize` on this platform.
+    RoundsOverflow,
+    /// A decoded cursor position is past its end, the end is past the range, or the range overflows past its offset.
+    InvalidPosition,
+}
+
+impl fmt::Display for FromBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength(len) => write!(f, "invalid encoded token length {len}"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported algorithm version {version}"),
+            Self::ChecksumMismatch => f.write_str("checksum mismatch"),
+            Self::RoundsOverflow => f.write_str("round count doesn't fit in a usize"),
+            Self::InvalidPosition => f.write_str("cursor position is outside of the range"),
+        }
+    }
+}
+
+impl core::error::Error for FromBytesError {}
+
+#[cfg(feature = "std")]
+/// Returned by [`BlackRockGenerator::verify_against`](crate::generator::BlackRockGenerator::verify_against).
+///
+/// Line numbers start at 1.
+#[derive(Debug)]
+pub enum VerifyError {
+    /// Reading the reference failed.
+    Io(std::io::Error),
+    /// A line isn't an `index,expected_output` pair of integers.
+    Parse { line: usize },
+    /// The index on a line is outside of the generator's range.
+    OutOfRange { line: usize, index: u64 },
+    /// `shuffle(index)` doesn't produce the expected output.
+    Mismatch { line: usize, index: u64, expected: u64, actual: u64 },
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read reference: {err}"),
+            Self::Parse { line } => write!(f, "line {line}: expected `index,expected_output`"),
+            Self::OutOfRange { line, index } => write!(f, "line {line}: index {index} is outside of the range"),
+            Self::Mismatch { line, index, expected, actual } => {
+                write!(f, "line {line}: shuffle({index}) is {actual}, expected {expected}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for VerifyError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Returned by [`BlackRockGenerator::try_permutation`](crate::generator::BlackRockGenerator::try_permutation)
+/// when the permutation is larger than allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaterializeError {
+    /// What the permutation would take, see [`materialized_bytes`](crate::generator::BlackRockGenerator::materialized_bytes).
+    pub bytes: u64,
+    /// The limit that was passed in.
+    pub max_bytes: u64,
+}
+
+impl fmt::Display for MaterializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "materializing the permutation takes {} bytes, more than the limit of {}", self.bytes, self.max_bytes)
+    }
+}
+
+impl core::error::Error for MaterializeError {}
+
+/// Returned by [`BlackRockIter::from_range_bounds`](crate::BlackRockIter::from_range_bounds).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeBoundsError {
+    /// The range has no upper bound, e.g. `100..`.
+    Unbounded,
+    /// The range holds 2<sup>64</sup> values, e.g. `..=u64::MAX`, which don't fit in a `u64` length.
+    TooLong,
+}
+
+impl fmt::Display for RangeBoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unbounded => f.write_str("range has no upper bound"),
+            Self::TooLong => f.write_str("range holds more than u64::MAX values"),
+        }
+    }
+}
+
+impl core::error::Error for RangeBoundsError {}
@@ -0,0 +1,119 @@
+//! The Feistel network shared by [`BlackRockGenerator`](crate::generator::BlackRockGenerator) and
+//! [`BlackRockGenerator128`](crate::generator128::BlackRockGenerator128).
+//!
+//! Both halves are at most 64 bits wide whatever the range, so the rounds always run on `u64`s,
+//! only splitting a value into halves and joining them back depends on the width of the range.
+
+/// A value of the padded domain, split into a low half of `a_bits` bits and the high half above it.
+pub(crate) trait Block: Copy {
+    /// `(low, high)`.
+    fn split(self, a_bits: u32, a_mask: u64) -> (u64, u64);
+    fn join(high: u64, low: u64, a_bits: u32) -> Self;
+}
+
+impl Block for u64 {
+    #[inline(always)]
+    fn split(self, a_bits: u32, a_mask: u64) -> (u64, u64) {
+        (self & a_mask, self >> a_bits)
+    }
+
+    #[inline(always)]
+    fn join(high: u64, low: u64, a_bits: u32) -> Self {
+        (high << a_bits) + low
+    }
+}
+
+impl Block for u128 {
+    #[inline(always)]
+    fn split(self, a_bits: u32, a_mask: u64) -> (u64, u64) {
+        (self as u64 & a_mask, (self >> a_bits) as u64)
+    }
+
+    #[inline(always)]
+    fn join(high: u64, low: u64, a_bits: u32) -> Self {
+        (u128::from(high) << a_bits) + u128::from(low)
+    }
+}
+
+/// The Feistel split of a range, the `a` half gets the low bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Split {
+    pub(crate) a_bits: u32,
+    pub(crate) a_mask: u64,
+    pub(crate) b_mask: u64,
+}
+
+impl Split {
+    /// The smallest power of two `a` above the square root of the range, and the smallest power of two `b`
+    /// with `a * b` above the range. Both are at most 2<sup>64</sup> for any `u128` range.
+    pub(crate) const fn new(range: u128) -> Self {
+        let a = (range.isqrt() + 1).next_power_of_two();
+        let b = ((range / a) + 1).next_power_of_two();
+
+        Self {
+            a_bits: a.trailing_zeros(),
+            a_mask: (a - 1) as u64,
+            b_mask: (b - 1) as u64,
+        }
+    }
+
+    /// `rounds` Feistel rounds over `m`, mixing round `j` with `round(j, right)`.
+    // always inlined so that a constant `rounds` unrolls the loop and resolves the final swap at compile time
+    #[inline(always)]
+    pub(crate) fn encrypt<B: Block>(self, m: B, rounds: usize, round: impl Fn(usize, u64) -> u64) -> B {
+        let (mut left, mut right) = m.split(self.a_bits, self.a_mask);
+
+        for j in 1..=rounds {
+            let mask = if j & 1 == 1 { self.a_mask } else { self.b_mask };
+            let tmp = left.wrapping_add(round(j, right)) & mask;
+            left = right;
+            right = tmp;
+        }
+
+        if rounds % 2 == 1 {
+            B::join(left, right, self.a_bits)
+        } else {
+            B::join(right, left, self.a_bits)
+        }
+    }
+
+    /// [`encrypt`](Self::encrypt) of `N` values at once, every round runs over all of them before the next one starts.
+    #[inline]
+    pub(crate) fn encrypt_lanes<B: Block, const N: usize>(self, m: [B; N], rounds: usize, round: impl Fn(usize, u64) -> u64) -> [B; N] {
+        let halves = m.map(|m| m.split(self.a_bits, self.a_mask));
+        let (mut left, mut right) = (halves.map(|(low, _)| low), halves.map(|(_, high)| high));
+
+        for j in 1..=rounds {
+            let mask = if j & 1 == 1 { self.a_mask } else { self.b_mask };
+            for i in 0..N {
+                let tmp = left[i].wrapping_add(round(j, right[i])) & mask;
+                left[i] = right[i];
+                right[i] = tmp;
+            }
+        }
+
+        core::array::from_fn(|i| {
+            if rounds % 2 == 1 {
+                B::join(left[i], right[i], self.a_bits)
+            } else {
+                B::join(right[i], left[i], self.a_bits)
+            }
+        })
+    }
+
+    /// [`encrypt`](Self::encrypt) run backwards, starting by undoing its final swap.
+    #[inline]
+    pub(crate) fn decrypt<B: Block>(self, c: B, rounds: usize, round: impl Fn(usize, u64) -> u64) -> B {
+        let (low, high) = c.split(self.a_bits, self.a_mask);
+        let (mut left, mut right) = if rounds % 2 == 1 { (high, low) } else { (low, high) };
+
+        for j in (1..=rounds).rev() {
+            let mask = if j & 1 == 1 { self.a_mask } else { self.b_mask };
+            let tmp = right.wrapping_sub(round(j, left)) & mask;
+            right = left;
+            left = tmp;
+        }
+
+        B::join(right, left, self.a_bits)
+    }
+}
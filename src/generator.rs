@@ -1,31 +1,245 @@
-// https://en.wikipedia.org/wiki/Integer_square_root
-const fn int_sqrt(n: u64) -> u64 {
-    if n <= 1 {
-        return n;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+use core::iter::FusedIterator;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::io::BufRead;
+use crate::BlackRockIter;
+use crate::feistel::Split;
+use crate::error::{FromBytesError, MaterializeError};
+#[cfg(feature = "std")]
+use crate::error::VerifyError;
+
+// https://github.com/mat-1/perfect_rand
+#[inline]
+const fn sipround((mut v0, mut v1, mut v2, mut v3): (u64, u64, u64, u64)) -> (u64, u64, u64, u64) {
+    v0 = v0.wrapping_add(v1);
+    v2 = v2.wrapping_add(v3);
+    v1 = v1.rotate_left(13) ^ v0;
+    v3 = v3.rotate_left(16) ^ v2;
+    v0 = v0.rotate_left(32);
+
+    v2 = v2.wrapping_add(v1);
+    v0 = v0.wrapping_add(v3);
+    v1 = v1.rotate_left(17) ^ v2;
+    v3 = v3.rotate_left(21) ^ v0;
+    v2 = v2.rotate_left(32);
+
+    (v0, v1, v2, v3)
+}
+
+// all zeroes will lead to an all-zero output,
+// this adds some randomness for that case.
+const SIP_V3: u64 = 0xf3016d19bc9ad940;
+
+#[inline]
+const fn sip_state(v0: u64, v1: u64, v2: u64) -> (u64, u64, u64, u64) {
+    (v0, v1, v2, SIP_V3)
+}
+
+/// The keyed mixing primitive behind every Feistel round,
+/// also reused wherever the crate needs a deterministic hash.
+#[inline]
+pub(crate) const fn sip(v0: u64, v1: u64, v2: u64) -> u64 {
+    let v = sipround(sip_state(v0, v1, v2));
+    let v = sipround(v);
+    let v = sipround(v);
+
+    sipround(v).0
+}
+
+// the part of the first sipround of `sip` that only depends on `v2`, i.e. the seed of a round.
+#[inline]
+pub(crate) const fn sip_seed_state(v2: u64) -> (u64, u64) {
+    let v2 = v2.wrapping_add(SIP_V3);
+    (v2, SIP_V3.rotate_left(16) ^ v2)
+}
+
+// `sip` with the seed half of the first sipround precomputed by `sip_seed_state`.
+#[inline]
+const fn sip_with_seed_state(mut v0: u64, mut v1: u64, (mut v2, mut v3): (u64, u64)) -> u64 {
+    v0 = v0.wrapping_add(v1);
+    v1 = v1.rotate_left(13) ^ v0;
+    v0 = v0.rotate_left(32);
+
+    v2 = v2.wrapping_add(v1);
+    v0 = v0.wrapping_add(v3);
+    v1 = v1.rotate_left(17) ^ v2;
+    v3 = v3.rotate_left(21) ^ v0;
+    v2 = v2.rotate_left(32);
+
+    let v = sipround((v0, v1, v2, v3));
+    let v = sipround(v);
+    sipround(v).0
+}
+
+fn materialized_len(range: u64) -> usize {
+    usize::try_from(range).expect("range is too large to materialize")
+}
+
+const CHECKSUM_KEY: u64 = 0x9e3779b97f4a7c15;
+const TENANT_TAG: u64 = u64::MAX - 1;
+// largest range `total_retries` walks exactly
+const EXACT_RETRIES_LIMIT: u64 = 1 << 24;
+const KEY_DERIVATION_KEY: u64 = 0x6a09e667f3bcc908;
+// values per batch in `shuffle_into` and `shuffle_many`
+const LANES: usize = 8;
+// values per rayon task in `permutation_par`
+#[cfg(feature = "rayon")]
+const PAR_CHUNK_LEN: usize = 1 << 14;
+
+/// The seed of [`BlackRockGenerator::deterministic`] and its iterator equivalents, the first 64 bits
+/// of the fractional part of pi. It never changes, so their order is the same across processes and crate versions.
+pub const DETERMINISTIC_SEED: u64 = 0x243f6a8885a308d3;
+
+// sip fold over the key in 8 byte words, the length goes in last
+// so that keys differing only by trailing zeros don't collide.
+fn sip_key(key: &[u8]) -> u64 {
+    let h = key.chunks(8).fold(0, |h, chunk| {
+        let mut word = [0; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sip(h, u64::from_le_bytes(word), KEY_DERIVATION_KEY)
+    });
+    sip(h, key.len() as u64, KEY_DERIVATION_KEY)
+}
+
+/// The keyed function mixed into every Feistel round of a [`BlackRockGenerator`].
+///
+/// `mix` can be anything deterministic, the Feistel network stays a permutation no matter how weak
+/// the function is, only the quality of the shuffle depends on it.
+pub trait RoundFunction {
+    /// Mix the right half `right` of round `j` under `seed`.
+    fn mix(&self, j: u64, right: u64, seed: u64) -> u64;
+
+    // what the generator actually calls, only `SipRound` overrides it to use the cached seed half of the
+    // sip state: `SeedState` can't be named outside of this crate, so other round functions can't override it
+    #[doc(hidden)]
+    #[inline]
+    fn mix_with_seed_state(&self, j: u64, right: u64, seed: u64, _: sealed::SeedState) -> u64 {
+        self.mix(j, right, seed)
     }
+}
+
+pub(crate) mod sealed {
+    /// The seed half of the sip state every round starts from, see `sip_seed_state`.
+    #[derive(Clone, Copy)]
+    pub struct SeedState(pub(crate) (u64, u64));
+}
 
-    let mut x0 = n / 2;
-    let mut x1 = (x0 + n / x0) / 2;
+/// The default [`RoundFunction`], four siprounds like masscan and perfect_rand.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SipRound;
 
-    while x1 < x0 {
-        x0 = x1;
-        x1 = (x0 + n / x0) / 2;
+impl RoundFunction for SipRound {
+    #[inline]
+    fn mix(&self, j: u64, right: u64, seed: u64) -> u64 {
+        sip(j, right, seed)
     }
 
-    x0
+    #[inline]
+    fn mix_with_seed_state(&self, j: u64, right: u64, _: u64, seed_state: sealed::SeedState) -> u64 {
+        sip_with_seed_state(j, right, seed_state.0)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
 #[must_use = "this generator does nothing unless driven"]
-pub struct BlackRockGenerator {
+pub struct BlackRockGenerator<F = SipRound> {
     range: u64,
     seed: u64,
     rounds: usize,
     a_bits: u32,
     a_mask: u64,
     b_mask: u64,
+    // the seed half of the sip state every round starts from, see `sip_seed_state`
+    seed_state: (u64, u64),
+    round_fn: F,
+}
+
+/// Only shows the config, `range`, `seed` and `rounds`, and the round function,
+/// the masks of the Feistel split are derived from the range and just noise in logs.
+impl<F: core::fmt::Debug> core::fmt::Debug for BlackRockGenerator<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BlackRockGenerator")
+            .field("range", &self.range)
+            .field("seed", &self.seed)
+            .field("rounds", &self.rounds)
+            .field("round_fn", &self.round_fn)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Scrubs the seed, and everything derived from it, the range and rounds are kept.
+///
+/// With the `zeroize` feature the generator is scrubbed on drop too, which rules out `Copy`:
+/// the generator and the wrappers holding one are only `Clone` then.
+#[cfg(feature = "zeroize")]
+impl<F> zeroize::Zeroize for BlackRockGenerator<F> {
+    fn zeroize(&mut self) {
+        self.seed.zeroize();
+        self.seed_state.0.zeroize();
+        self.seed_state.1.zeroize();
+        self.a_bits.zeroize();
+        self.a_mask.zeroize();
+        self.b_mask.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<F> Drop for BlackRockGenerator<F> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<F> zeroize::ZeroizeOnDrop for BlackRockGenerator<F> {}
+
+// what the serde impls read and write, the masks are always rederived from the range
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "BlackRockGenerator")]
+struct GeneratorConfig {
+    range: u64,
+    seed: u64,
+    rounds: usize,
+}
+
+/// Serialized as its config, `range`, `seed` and `rounds`, like [`to_bytes`](BlackRockGenerator::to_bytes).
+#[cfg(feature = "serde")]
+impl<F> serde::Serialize for BlackRockGenerator<F> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&GeneratorConfig { range: self.range, seed: self.seed, rounds: self.rounds }, serializer)
+    }
+}
+
+/// Only the config is read, the Feistel split is [rebuilt](BlackRockGenerator::rebuild) from it,
+/// with the round function's `Default`.
+#[cfg(feature = "serde")]
+impl<'de, F: RoundFunction + Default> serde::Deserialize<'de> for BlackRockGenerator<F> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let GeneratorConfig { range, seed, rounds } = <GeneratorConfig as serde::Deserialize>::deserialize(deserializer)?;
+        let mut generator = Self {
+            range,
+            seed,
+            rounds,
+            a_bits: 0,
+            a_mask: 0,
+            b_mask: 0,
+            seed_state: (0, 0),
+            round_fn: F::default(),
+        };
+        generator.rebuild();
+        Ok(generator)
+    }
 }
 
+#[cfg(feature = "rand")]
 impl Default for BlackRockGenerator {
     fn default() -> Self {
         Self::new(0)
@@ -37,112 +251,227 @@ impl BlackRockGenerator {
     /// Use [`BlackRockGenerator::new`] to use the default seed and rounds.
     ///
     /// - `range`: The highest value you will try to shuffle. For example, this
-    ///    would be 2<sup>32</sup> for an IPv4 address.
+    ///   would be 2<sup>32</sup> for an IPv4 address.
     /// - `seed`: The seed used for randomization.
     /// - `rounds`: The amount of times the randomization is done, to make it more random. Default is 3.
+    ///   `rounds = 0` is allowed and is the identity permutation, `shuffle(m) == m`: the Feistel split is
+    ///   undone unchanged, so it's still a bijection, just not a shuffled one.
+    ///
+    /// Every `u64` range is supported, up to `u64::MAX`: both halves of the Feistel split are at most
+    /// 2<sup>32</sup> wide, so the padded domain never exceeds 2<sup>64</sup>.
+    /// Tiny ranges need no special casing: the padded domain of `range = 1` is `{0, 1}` and the Feistel network
+    /// is a bijection on it, so cycle walking always ends on `0`, and likewise for every other small range.
     ///
     /// ```
     /// # use blackrock2::generator::BlackRockGenerator;
-    /// let perfect_rng = BlackRockGenerator::with_seed_and_rounds(10, rand::random(), 3);
+    /// let perfect_rng = BlackRockGenerator::with_seed_and_rounds(10, 0x5eed, 3);
     /// ```
     pub const fn with_seed_and_rounds(range: u64, seed: u64, rounds: usize) -> Self {
-        let a = (int_sqrt(range) + 1).next_power_of_two();
-        let b = ((range / a) + 1).next_power_of_two();
+        Self::with_round_function(range, seed, rounds, SipRound)
+    }
 
-        #[inline]
-        const fn bit_count(x: u64) -> u32 {
-            match x.checked_ilog2() {
-                Some(x) => x,
-                None => 0
-            }
+    /// Create a new `BlackRockGenerator` with the fixed [`DETERMINISTIC_SEED`] and default rounds,
+    /// for benchmarks and regression tests that need the same order on every run.
+    pub const fn deterministic(range: u64) -> Self {
+        Self::with_seed_and_rounds(range, DETERMINISTIC_SEED, 3)
+    }
+
+    /// Like [`with_seed_and_rounds`](Self::with_seed_and_rounds), but in debug builds also checks that
+    /// the result is a permutation, see [`debug_verified`](Self::debug_verified).
+    ///
+    /// # Panics
+    /// in debug builds if the generator isn't a permutation, or the range doesn't fit in memory.
+    #[track_caller]
+    pub fn with_seed_and_rounds_verified(range: u64, seed: u64, rounds: usize) -> Self {
+        Self::with_seed_and_rounds(range, seed, rounds).debug_verified()
+    }
+
+    /// Create a permutation of `0..range` that maps every value in `anchors` to itself
+    /// and shuffles the rest among themselves.
+    ///
+    /// # Panics
+    /// if any anchor is outside of the range.
+    pub fn with_anchors(range: u64, seed: u64, rounds: usize, anchors: &[u64]) -> AnchoredGenerator {
+        let mut anchors = anchors.to_vec();
+        anchors.sort_unstable();
+        anchors.dedup();
+
+        if let Some(&last) = anchors.last() {
+            assert!(last < range, "anchor {last} is outside of the range {range}");
         }
 
-        Self {
-            range,
-            seed,
-            rounds,
-            a_bits: bit_count(a),
-            a_mask: a - 1,
-            b_mask: b - 1,
+        AnchoredGenerator {
+            generator: Self::with_seed_and_rounds(range - anchors.len() as u64, seed, rounds),
+            anchors,
+        }
+    }
+
+    /// Brute force a seed whose permutation maps every `(index, output)` pair in `constraints`,
+    /// trying the seeds `0..max_tries` in order.
+    ///
+    /// Returns `None` if no seed in that span works or a constraint is outside of the range.
+    /// Every extra constraint makes a match roughly `range` times less likely, so keep them few.
+    pub fn find_seed_for(range: u64, rounds: usize, constraints: &[(u64, u64)], max_tries: u64) -> Option<u64> {
+        if constraints.iter().any(|&(index, output)| index >= range || output >= range) {
+            return None;
         }
+
+        (0..max_tries).find(|&seed| {
+            let generator = Self::with_seed_and_rounds(range, seed, rounds);
+            constraints.iter().all(|&(index, output)| generator.shuffle(index) == output)
+        })
+    }
+
+    /// Create the generator of one tenant, mixing `master_seed` and `tenant_id` into the seed.
+    ///
+    /// The same tenant always gets the same order, different tenants get unrelated orders.
+    pub const fn for_tenant(range: u64, master_seed: u64, tenant_id: u64, rounds: usize) -> Self {
+        Self::with_seed_and_rounds(range, sip(TENANT_TAG, tenant_id, master_seed), rounds)
+    }
+
+    /// Create a generator whose seed is derived from an arbitrary byte `key`, e.g. a passphrase
+    /// or a scan id, with the built-in sip based derivation.
+    ///
+    /// Use [`with_key_and_hasher`](Self::with_key_and_hasher) to derive the seed with a different function.
+    pub fn with_key(range: u64, key: &[u8], rounds: usize) -> Self {
+        Self::with_key_and_hasher(range, key, rounds, sip_key)
+    }
+
+    /// Like [`with_key`](Self::with_key), but derives the seed with `hasher`, e.g. a stronger KDF like BLAKE3
+    /// truncated to 64 bits.
+    pub fn with_key_and_hasher<H: Fn(&[u8]) -> u64>(range: u64, key: &[u8], rounds: usize, hasher: H) -> Self {
+        Self::with_seed_and_rounds(range, hasher(key), rounds)
     }
 
+    /// Create a generator for scanning exactly `count` targets, shuffling `0..count`.
+    ///
+    /// The same as [`with_seed_and_rounds`](Self::with_seed_and_rounds), the name just makes clear
+    /// that `count` is the number of targets and not the power of two domain the cipher works in.
+    pub const fn for_target_count(count: u64, seed: u64, rounds: usize) -> Self {
+        Self::with_seed_and_rounds(count, seed, rounds)
+    }
 
     /// Create a new `BlackRockGenerator` with the provided seed and default rounds.
     pub fn with_seed(range: u64, seed: u64) -> Self {
         Self::with_seed_and_rounds(range, seed, 3)
     }
 
+    /// Create a new `BlackRockGenerator` with the provided seed, picking the rounds from the size of the range.
+    ///
+    /// Wider halves need more rounds to mix well, so this uses `3 + a_bits / 8` rounds
+    /// where `a_bits` is the width of the low half of the Feistel split;
+    /// 3 rounds below a range of about 2<sup>14</sup>, 5 rounds for an IPv4 range, up to 7 rounds for the full `u64` space.
+    pub const fn with_auto_rounds(range: u64, seed: u64) -> Self {
+        let mut generator = Self::with_seed_and_rounds(range, seed, 0);
+        generator.rounds = 3 + generator.a_bits as usize / 8;
+        generator
+    }
+
+    #[cfg(feature = "rand")]
     /// Create a new `BlackRockGenerator` with a random seed and the provided rounds.
     pub fn with_rounds(range: u64, rounds: usize) -> Self {
         Self::with_seed_and_rounds(range, rand::random(), rounds)
     }
 
+    #[cfg(feature = "rand")]
     /// Create a new `BlackRockGenerator` with a random seed and default rounds.
     pub fn new(range: u64) -> Self {
         Self::with_seed_and_rounds(range, rand::random(), 3)
     }
+}
+
+impl<F: RoundFunction> BlackRockGenerator<F> {
+    /// Like [`with_seed_and_rounds`](BlackRockGenerator::with_seed_and_rounds), but mixes every round with `round_fn`
+    /// instead of [`SipRound`].
+    pub const fn with_round_function(range: u64, seed: u64, rounds: usize, round_fn: F) -> Self {
+        let mut generator = Self {
+            range,
+            seed,
+            rounds,
+            a_bits: 0,
+            a_mask: 0,
+            b_mask: 0,
+            seed_state: (0, 0),
+            round_fn,
+        };
+        generator.rebuild();
+        generator
+    }
 
+    /// Recompute the Feistel split (`a_bits`, `a_mask` and `b_mask`) from the current range,
+    /// and the cached seed half of the round state from the current seed.
+    ///
+    /// These are derived purely from `range` and `seed`, so anything that reconstructs a generator
+    /// field by field has to call this instead of trusting stored masks, like the serde `Deserialize` impl does:
+    /// mismatched masks don't give a bijection.
+    pub const fn rebuild(&mut self) {
+        let split = Split::new(self.range as u128);
+        self.a_bits = split.a_bits;
+        self.a_mask = split.a_mask;
+        self.b_mask = split.b_mask;
+        self.seed_state = sip_seed_state(self.seed);
+    }
 
-    // https://github.com/mat-1/perfect_rand
     #[inline]
-    fn sipround(&self, (mut v0, mut v1, mut v2, mut v3): (u64, u64, u64, u64)) -> (u64, u64, u64, u64) {
-        v0 = v0.wrapping_add(v1);
-        v2 = v2.wrapping_add(v3);
-        v1 = v1.rotate_left(13) ^ v0;
-        v3 = v3.rotate_left(16) ^ v2;
-        v0 = v0.rotate_left(32);
+    fn round(&self, j: usize, right: u64) -> u64 {
+        self.round_fn.mix_with_seed_state(j as u64, right, self.seed, sealed::SeedState(self.seed_state))
+    }
 
-        v2 = v2.wrapping_add(v1);
-        v0 = v0.wrapping_add(v3);
-        v1 = v1.rotate_left(17) ^ v2;
-        v3 = v3.rotate_left(21) ^ v0;
-        v2 = v2.rotate_left(32);
+    /// The number of values this generator shuffles, it permutes `0..range`.
+    #[inline]
+    pub const fn range(&self) -> u64 {
+        self.range
+    }
 
-        (v0, v1, v2, v3)
+    /// The seed the rounds are keyed with, e.g. to log the one [`new`](Self::new) picked at random.
+    #[inline]
+    pub const fn seed(&self) -> u64 {
+        self.seed
     }
 
+    /// The number of Feistel rounds.
+    ///
+    /// Everything else is derived from these three, so
+    /// `with_seed_and_rounds(g.range(), g.seed(), g.rounds())` rebuilds the same generator.
     #[inline]
-    fn round(&self, j: usize, right: u64) -> u64 {
-        let v0 = j as u64;
-        let v1 = right;
-        let v2 = self.seed;
-        // all zeroes will lead to an all-zero output,
-        // this adds some randomness for that case.
-        let v3: u64 = 0xf3016d19bc9ad940;
+    pub const fn rounds(&self) -> usize {
+        self.rounds
+    }
 
-        let v = self.sipround((v0, v1, v2, v3));
-        let v = self.sipround(v);
-        let v = self.sipround(v);
+    /// The full config, `(range, seed, rounds)`, e.g. to compare generators across a
+    /// [`to_bytes`](Self::to_bytes) round trip.
+    #[inline]
+    pub const fn config(&self) -> (u64, u64, usize) {
+        (self.range, self.seed, self.rounds)
+    }
 
-        self.sipround(v).0
+    /// Keyed hash of `x` under this generator's seed, `tag` separates the different uses.
+    /// Rounds use `tag = j`, so tags should stay well clear of any sane round count.
+    #[inline]
+    pub(crate) const fn hash(&self, tag: u64, x: u64) -> u64 {
+        sip(tag, x, self.seed)
     }
 
     #[inline]
     fn encrypt(&self, m: u64) -> u64 {
-        let mut left = m & self.a_mask;
-        let mut right = m >> self.a_bits;
-
-        let mut j = 1;
-        while j <= self.rounds {
-            if j & 1 == 1 {
-                let tmp = (left + self.round(j, right)) & self.a_mask;
-                left = right;
-                right = tmp;
-                j += 1;
-            } else {
-                let tmp = (left + self.round(j, right)) & self.b_mask;
-                left = right;
-                right = tmp;
-                j += 1;
-            }
-        }
+        self.encrypt_rounds(m, self.rounds)
+    }
 
-        if j % 2 == 0 {
-            (left << self.a_bits) + right
-        } else {
-            (right << self.a_bits) + left
-        }
+    #[inline]
+    const fn split(&self) -> Split {
+        Split { a_bits: self.a_bits, a_mask: self.a_mask, b_mask: self.b_mask }
+    }
+
+    // always inlined so that a constant `rounds` unrolls the loop and resolves the final swap at compile time
+    #[inline(always)]
+    fn encrypt_rounds(&self, m: u64, rounds: usize) -> u64 {
+        self.split().encrypt(m, rounds, |j, right| self.round(j, right))
+    }
+
+    // `encrypt` run backwards, only a bijection on the padded domain, `unshuffle` adds the inverse cycle walk
+    #[inline]
+    fn decrypt(&self, c: u64) -> u64 {
+        self.split().decrypt(c, self.rounds, |j, left| self.round(j, left))
     }
 
     pub fn shuffle(&self, m: u64) -> u64 {
@@ -152,53 +481,1694 @@ impl BlackRockGenerator {
         }
         c
     }
+
+    /// Like [`shuffle`](Self::shuffle), but with the round count fixed at compile time, so the Feistel
+    /// loop can be fully unrolled in hot loops.
+    ///
+    /// `ROUNDS` replaces the configured [`rounds`](Self::rounds), the output matches `shuffle` on a generator
+    /// with the same range and seed and `ROUNDS` rounds.
+    pub fn shuffle_const<const ROUNDS: usize>(&self, m: u64) -> u64 {
+        let mut c = self.encrypt_rounds(m, ROUNDS);
+        while c >= self.range {
+            c = self.encrypt_rounds(c, ROUNDS);
+        }
+        c
+    }
+
+    /// The inverse of [`shuffle`](Self::shuffle), `unshuffle(shuffle(m)) == m` for every `m < range`,
+    /// e.g. to recover the position in the scan order of the value a response came back on.
+    ///
+    /// Runs the Feistel rounds backwards, starting by undoing the final swap, which depends on whether
+    /// the round count is odd or even. Cycle walking is inverted the same way: decrypt again while the
+    /// value is outside of the range, which retraces the forward walk.
+    ///
+    /// `c` must be less than `range`.
+    ///
+    /// ```
+    /// # use blackrock2::generator::BlackRockGenerator;
+    /// let generator = BlackRockGenerator::with_seed(1000, 0x5eed);
+    /// assert!((0..1000).all(|m| generator.unshuffle(generator.shuffle(m)) == m));
+    /// ```
+    pub fn unshuffle(&self, c: u64) -> u64 {
+        debug_assert!(c < self.range, "{c} is outside of the range {}", self.range);
+
+        let mut m = self.decrypt(c);
+        while m >= self.range {
+            m = self.decrypt(m);
+        }
+        m
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Iterate over the shuffled range, see [`shuffled_range`](BlackRockGenerator::shuffled_range).
+impl IntoIterator for BlackRockGenerator {
+    type Item = u64;
+    type IntoIter = BlackRockIter;
 
-    fn verify(range: u64, seed: u64, rounds: usize) {
-        let randomizer = BlackRockGenerator::with_seed_and_rounds(range, seed, rounds);
-        println!("randomizer: {randomizer:?}");
+    fn into_iter(self) -> Self::IntoIter {
+        self.shuffled_range()
+    }
+}
 
-        // make sure every number gets added exactly once
-        let mut list = vec![0; range as usize];
-        for i in 0..range {
-            let x = randomizer.shuffle(i) as usize;
-            list[x] += 1;
+impl BlackRockGenerator {
+    /// The sip state round `j` starts from when mixing `right`, before any sipround is applied.
+    /// Exposed for test vectors and for auditing against the masscan and perfect_rand references.
+    #[doc(hidden)]
+    pub const fn debug_round_state(&self, j: usize, right: u64) -> (u64, u64, u64, u64) {
+        sip_state(j as u64, right, self.seed)
+    }
+
+    /// Version of the shuffling algorithm, stored in [`to_bytes`](Self::to_bytes) so that
+    /// a token can't silently decode into a different ordering after the algorithm changes.
+    pub const ALGORITHM_VERSION: u8 = 1;
+
+    /// Length of the output of [`to_bytes`](Self::to_bytes).
+    pub const ENCODED_LEN: usize = 1 + 3 * 8 + 8;
+
+    pub(crate) fn checksum(header: &[u8]) -> u64 {
+        header.chunks(8).fold(0, |h, chunk| {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            sip(h, u64::from_le_bytes(word), CHECKSUM_KEY)
+        })
+    }
+
+    /// Encode this generator as a compact token that reproduces the exact same ordering.
+    ///
+    /// Only the config is stored, the layout is the [`ALGORITHM_VERSION`](Self::ALGORITHM_VERSION) byte,
+    /// then `range`, `seed` and `rounds` as little endian `u64`s, then a checksum of all of the above.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LEN);
+        bytes.push(Self::ALGORITHM_VERSION);
+        bytes.extend_from_slice(&self.range.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(self.rounds as u64).to_le_bytes());
+        bytes.extend_from_slice(&Self::checksum(&bytes).to_le_bytes());
+        bytes
+    }
+
+    /// Decode a token written by [`to_bytes`](Self::to_bytes), validating its version and checksum.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FromBytesError> {
+        let bytes = <&[u8; Self::ENCODED_LEN]>::try_from(bytes)
+            .map_err(|_| FromBytesError::InvalidLength(bytes.len()))?;
+
+        let (header, checksum) = bytes.split_at(Self::ENCODED_LEN - 8);
+        if Self::checksum(header).to_le_bytes() != checksum {
+            return Err(FromBytesError::ChecksumMismatch);
         }
 
-        for (i, number) in list.into_iter().enumerate() {
-            assert_eq!(number, 1, "Index: {i}, range: {range:?}");
+        if header[0] != Self::ALGORITHM_VERSION {
+            return Err(FromBytesError::UnsupportedVersion(header[0]));
         }
+
+        let word = |i: usize| u64::from_le_bytes(header[1 + i * 8..][..8].try_into().unwrap());
+        let rounds = usize::try_from(word(2)).map_err(|_| FromBytesError::RoundsOverflow)?;
+        Ok(Self::with_seed_and_rounds(word(0), word(1), rounds))
     }
+}
 
-    #[test]
-    fn verify_ranges() {
-        let mut range = 3015 * 3;
+impl<F: RoundFunction> BlackRockGenerator<F> {
+    #[cfg(feature = "std")]
+    /// Check this generator against a reference of `index,expected_output` lines, e.g. produced by
+    /// another implementation, stopping at the first line that doesn't match.
+    ///
+    /// Whitespace around both numbers is ignored, as are empty lines and lines starting with `#`.
+    pub fn verify_against<R: BufRead>(&self, reader: R) -> Result<(), VerifyError> {
+        for (line, content) in (1..).zip(reader.lines()) {
+            let content = content?;
+            let content = content.trim();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
 
-        for i in 0..5 {
-            range += 11 + i;
-            range *= 1 + i;
+            let (index, expected) = content.split_once(',')
+                .and_then(|(index, expected)| Some((index.trim().parse().ok()?, expected.trim().parse().ok()?)))
+                .ok_or(VerifyError::Parse { line })?;
 
-            verify(range, 0, 6);
+            if index >= self.range {
+                return Err(VerifyError::OutOfRange { line, index });
+            }
+
+            let actual = self.shuffle(index);
+            if actual != expected {
+                return Err(VerifyError::Mismatch { line, index, expected, actual });
+            }
         }
 
-        verify(10, 0, 3);
-        verify(100, 0, 3);
+        Ok(())
     }
 
-    #[test]
-    fn dont_get_stuck() {
-        for range in [10, 100] {
-            for seed in 0..100 {
-                let randomizer = BlackRockGenerator::with_seed_and_rounds(range, seed, 3);
+    /// Estimate how long shuffling the whole range takes at `rate_per_sec` values per second,
+    /// e.g. to tell the user a scan takes about 3.2 hours at 10k packets per second.
+    ///
+    /// Returns [`Duration::MAX`] when the rate is zero, negative or NaN,
+    /// or when the estimate is too large to represent.
+    pub fn estimated_duration(&self, rate_per_sec: f64) -> Duration {
+        if rate_per_sec.is_nan() || rate_per_sec <= 0.0 {
+            return Duration::MAX;
+        }
 
-                for i in 0..range {
-                    let _ = randomizer.shuffle(i);
+        Duration::try_from_secs_f64(self.range as f64 / rate_per_sec).unwrap_or(Duration::MAX)
+    }
+
+    /// Count the outputs over the whole range by their top `top_bits` bits,
+    /// where the bit width is the one needed to represent `range - 1`.
+    ///
+    /// For an IPv4 range with `top_bits = 8` this is the number of addresses landing in each /8.
+    /// Over the whole range every bijection gives the same counts, they are fixed by the range alone,
+    /// so this only catches a broken permutation. Use [`prefix_high_bit_histogram`](Self::prefix_high_bit_histogram)
+    /// to measure how evenly the first outputs spread. Walks the full range, so it costs O(range).
+    ///
+    /// # Panics
+    /// if `top_bits` is larger than the bit width of the range, or there are too many buckets to allocate.
+    pub fn high_bit_histogram(&self, top_bits: u32) -> Vec<u64> {
+        self.prefix_high_bit_histogram(top_bits, self.range)
+    }
+
+    /// Like [`high_bit_histogram`](Self::high_bit_histogram), but only counts the first `samples` outputs,
+    /// e.g. to check that the start of a scan already spreads across every /8.
+    ///
+    /// # Panics
+    /// if `top_bits` is larger than the bit width of the range, or there are too many buckets to allocate.
+    pub fn prefix_high_bit_histogram(&self, top_bits: u32, samples: u64) -> Vec<u64> {
+        let width = u64::BITS - self.range.saturating_sub(1).leading_zeros();
+        assert!(top_bits <= width, "can't take the top {top_bits} bits of a {width} bit range");
+
+        let buckets = 1usize.checked_shl(top_bits)
+            .unwrap_or_else(|| panic!("2^{top_bits} buckets don't fit in memory"));
+
+        let shift = width - top_bits;
+        let mut counts = vec![0; buckets];
+        for i in 0..samples.min(self.range) {
+            counts[(self.shuffle(i) >> shift) as usize] += 1;
+        }
+        counts
+    }
+
+    // the padded domain the Feistel network permutes, 2^64 for the largest ranges
+    #[inline]
+    const fn domain(&self) -> u128 {
+        (self.b_mask as u128 + 1) << self.a_bits
+    }
+
+    /// The chance that a single encryption lands outside of the range and has to be retried,
+    /// `1 - range / domain` where `domain` is the padded power of two domain of the Feistel network.
+    pub fn retry_probability(&self) -> f64 {
+        1.0 - self.range as f64 / self.domain() as f64
+    }
+
+    /// A one line summary of the Feistel split, e.g.
+    /// `range=1000 → a=32 (5 bits) × b=32 (5 bits) → domain=1024, retry_prob=2.3%`.
+    ///
+    /// Handy to see why some ranges are slower than others: the further the domain is above the range,
+    /// the more often [`shuffle`](Self::shuffle) has to retry.
+    pub fn describe_split(&self) -> String {
+        format!(
+            "range={} → a={} ({} bits) × b={} ({} bits) → domain={}, retry_prob={:.1}%",
+            self.range,
+            1u128 << self.a_bits,
+            self.a_bits,
+            u128::from(self.b_mask) + 1,
+            self.b_mask.count_ones(),
+            self.domain(),
+            self.retry_probability() * 100.0,
+        )
+    }
+
+    /// The total number of re-encryptions cycle walking does when shuffling every value of the range once.
+    ///
+    /// Ranges up to 2<sup>24</sup> are walked exactly. Larger ranges are estimated from [`retry_probability`](Self::retry_probability):
+    /// `range * p / (1 - p)` expected retries, which comes down to `domain - range`. That estimate is also an upper bound,
+    /// every value outside of the range is retried at most once over a full scan.
+    pub fn total_retries(&self) -> u64 {
+        if self.range == 0 {
+            return 0;
+        }
+
+        if self.range > EXACT_RETRIES_LIMIT {
+            return (self.domain() - u128::from(self.range)) as u64;
+        }
+
+        (0..self.range).map(|m| u64::from(self.shuffle_with_steps(m).1 - 1)).sum()
+    }
+
+    /// [`shuffle`](Self::shuffle) that also returns how many encryptions cycle walking took, at least 1.
+    ///
+    /// Everything above 1 is a retry because the encryption landed outside of the range,
+    /// see [`retry_probability`](Self::retry_probability).
+    pub fn shuffle_with_steps(&self, m: u64) -> (u64, u32) {
+        let mut steps = 1;
+        let mut c = self.encrypt(m);
+        while c >= self.range {
+            c = self.encrypt(c);
+            steps += 1;
+        }
+        (c, steps)
+    }
+
+    /// The longest cycle walk, in encryptions, of any value in `sample`, 0 for an empty sample.
+    ///
+    /// Compare it across candidate ranges and round counts to find a config that wastes little time re-encrypting.
+    pub fn max_walk_over(&self, sample: impl IntoIterator<Item = u64>) -> u32 {
+        sample.into_iter().map(|m| self.shuffle_with_steps(m).1).max().unwrap_or(0)
+    }
+
+    /// Returns `true` if `shuffle` maps `0..range` onto itself without collisions, i.e. is a bijection,
+    /// e.g. to self-check an unusual `range` and `rounds` config or a custom [`RoundFunction`].
+    ///
+    /// Walks the full range into a bitset, so it takes O(range) time and `range / 8` bytes and is meant
+    /// for small ranges and tests. Use [`first_collision`](Self::first_collision) to find out what collides.
+    /// Like there, no public configuration can make this `false`, it guards the implementation.
+    ///
+    /// # Panics
+    /// if the range doesn't fit in memory addressable by `usize`.
+    pub fn is_permutation(&self) -> bool {
+        let mut seen = vec![0u64; materialized_len(self.range).div_ceil(64)];
+        (0..self.range).all(|m| {
+            let c = self.shuffle(m);
+            let (word, bit) = ((c / 64) as usize, 1 << (c % 64));
+            let fresh = c < self.range && seen[word] & bit == 0;
+            if fresh {
+                seen[word] |= bit;
+            }
+            fresh
+        })
+    }
+
+    /// Check [`is_permutation`](Self::is_permutation) in debug builds and pass the generator through,
+    /// e.g. `BlackRockGenerator::with_round_function(range, seed, rounds, round_fn).debug_verified()`.
+    /// Does nothing with `debug_assertions` off. See also [`with_seed_and_rounds_verified`](BlackRockGenerator::with_seed_and_rounds_verified).
+    ///
+    /// # Panics
+    /// in debug builds if the generator isn't a permutation, or the range doesn't fit in memory.
+    #[track_caller]
+    pub fn debug_verified(self) -> Self {
+        debug_assert!(self.is_permutation(), "generator over {} with {} rounds is not a permutation", self.range, self.rounds);
+        self
+    }
+
+    /// The first pair of indices `(i, j)` with `i < j` and `shuffle(i) == shuffle(j)`,
+    /// or `None` if the permutation is a bijection as it should be.
+    ///
+    /// A runtime check for the invariant everything else relies on, walks the full range
+    /// and keeps one `u64` per value, so it is meant for small ranges.
+    ///
+    /// A Feistel network is a bijection whatever its round function, so no public configuration,
+    /// not even a deliberately broken [`RoundFunction`], makes this return `Some`. Only a Feistel split
+    /// that doesn't match the range, which the constructors never produce, can collide.
+    ///
+    /// # Panics
+    /// if the range doesn't fit in memory addressable by `usize`.
+    pub fn first_collision(&self) -> Option<(u64, u64)> {
+        let mut first_index = vec![u64::MAX; materialized_len(self.range)];
+        (0..self.range).find_map(|j| {
+            let slot = &mut first_index[self.shuffle(j) as usize];
+            match *slot {
+                u64::MAX => {
+                    *slot = j;
+                    None
                 }
+                i => Some((i, j)),
             }
+        })
+    }
+
+    /// Returns `true` if more than `threshold` of the range are fixed points (`shuffle(x) == x`).
+    ///
+    /// Tiny ranges have few possible permutations, so some seeds land close to the identity;
+    /// this flags those configs. Walks the full range, so it is meant for small ranges.
+    pub fn is_degenerate(&self, threshold: f64) -> bool {
+        if self.range == 0 {
+            return false;
+        }
+
+        let fixed = (0..self.range).filter(|&x| self.shuffle(x) == x).count();
+        fixed as f64 / self.range as f64 > threshold
+    }
+
+    #[cfg(feature = "std")]
+    /// Values that both `self` and `other` emit within their first `k` outputs,
+    /// in the order `self` emits them.
+    ///
+    /// Costs O(k) time and memory.
+    pub fn common_prefix_targets(&self, other: &Self, k: u64) -> Vec<u64> {
+        let theirs = (0..k.min(other.range))
+            .map(|i| other.shuffle(i))
+            .collect::<HashSet<_>>();
+
+        (0..k.min(self.range))
+            .map(|i| self.shuffle(i))
+            .filter(|x| theirs.contains(x))
+            .collect()
+    }
+
+    /// Mean number of differing bits between consecutive outputs `shuffle(i)` and `shuffle(i + 1)`
+    /// over the first `samples` pairs, a quick mixing-quality metric.
+    ///
+    /// A well mixed permutation lands close to half the bit width of the range.
+    /// Returns 0 if there are no pairs to compare.
+    pub fn avg_consecutive_hamming(&self, samples: u64) -> f64 {
+        let pairs = samples.min(self.range.saturating_sub(1));
+        if pairs == 0 {
+            return 0.0;
+        }
+
+        let total = (0..pairs)
+            .map(|i| u64::from((self.shuffle(i) ^ self.shuffle(i + 1)).count_ones()))
+            .sum::<u64>();
+
+        total as f64 / pairs as f64
+    }
+
+    /// Pearson's chi-square statistic of the outputs against an ideal random permutation.
+    ///
+    /// Positions and values are both split into `buckets` equal buckets and every pair is counted
+    /// in a `buckets × buckets` table, so it measures how much the bucket of `shuffle(i)` depends on
+    /// the bucket of `i` (only bucketing the values would always be perfectly uniform for a permutation).
+    /// An ideal permutation gives roughly a chi-square distribution with `(buckets - 1)²` degrees of freedom,
+    /// the identity is the worst case.
+    ///
+    /// Walks the full range, so it costs O(range) time and O(buckets²) memory. Returns 0 for an empty range.
+    ///
+    /// # Panics
+    /// if `buckets` is zero.
+    pub fn chi_square_uniformity(&self, buckets: usize) -> f64 {
+        assert_ne!(buckets, 0, "need at least one bucket");
+        if self.range == 0 {
+            return 0.0;
+        }
+
+        let bucket = |x: u64| (u128::from(x) * buckets as u128 / u128::from(self.range)) as usize;
+
+        let mut table = vec![0u64; buckets * buckets];
+        let mut sizes = vec![0u64; buckets];
+        for i in 0..self.range {
+            table[bucket(i) * buckets + bucket(self.shuffle(i))] += 1;
+            sizes[bucket(i)] += 1;
         }
+
+        // position and value buckets have the same sizes
+        let range = self.range as f64;
+        (0..buckets * buckets)
+            .filter(|&cell| sizes[cell / buckets] != 0 && sizes[cell % buckets] != 0)
+            .map(|cell| {
+                let expected = sizes[cell / buckets] as f64 * sizes[cell % buckets] as f64 / range;
+                let diff = table[cell] as f64 - expected;
+                diff * diff / expected
+            })
+            .sum()
+    }
+
+    /// The order of the permutation, the smallest `n > 0` such that applying
+    /// [`shuffle`](Self::shuffle) `n` times maps every value back to itself.
+    /// This is the LCM of all cycle lengths, an empty range has order 1.
+    ///
+    /// Walks every cycle with a visited flag per value, so it costs O(range) time and memory;
+    /// meant for small ranges. The LCM can grow past `u64` even for modest ranges,
+    /// in which case this saturates at `u64::MAX`.
+    ///
+    /// # Panics
+    /// if the range doesn't fit in memory addressable by `usize`.
+    pub fn permutation_order(&self) -> u64 {
+        const fn gcd(mut a: u64, mut b: u64) -> u64 {
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        }
+
+        let mut visited = vec![false; materialized_len(self.range)];
+        let mut order = 1u64;
+        for start in 0..self.range {
+            if visited[start as usize] {
+                continue;
+            }
+
+            let mut len = 0;
+            let mut x = start;
+            while !visited[x as usize] {
+                visited[x as usize] = true;
+                x = self.shuffle(x);
+                len += 1;
+            }
+
+            order = (order / gcd(order, len)).saturating_mul(len);
+        }
+        order
+    }
+
+    /// `k` disjoint random samples of `m` values each, the `i`th sample is `shuffle(i * m..(i + 1) * m)`.
+    ///
+    /// The permutation never repeats a value, so the samples can't overlap.
+    /// Returns `None` if `k * m` is larger than the range.
+    pub fn disjoint_samples(&self, k: usize, m: u64) -> Option<Vec<Vec<u64>>> {
+        (k as u64).checked_mul(m).filter(|&total| total <= self.range)?;
+
+        let samples = (0..k as u64)
+            .map(|i| (i * m..(i + 1) * m).map(|x| self.shuffle(x)).collect())
+            .collect();
+        Some(samples)
+    }
+
+    /// Collect the whole permutation, `permutation()[i] == shuffle(i)`.
+    ///
+    /// # Panics
+    /// if the range doesn't fit in memory addressable by `usize`.
+    pub fn permutation(&self) -> Vec<u64> {
+        let mut out = vec![0; materialized_len(self.range)];
+        self.shuffle_chunk(0, &mut out);
+        out
+    }
+
+    /// How many bytes [`permutation`](Self::permutation) allocates, `range * 8`, saturating at `u64::MAX`.
+    pub const fn materialized_bytes(&self) -> u64 {
+        self.range.saturating_mul(size_of::<u64>() as u64)
+    }
+
+    /// Like [`permutation`](Self::permutation), but refuses to allocate more than `max_bytes`
+    /// instead of running out of memory.
+    ///
+    /// Also fails past `isize::MAX` bytes, the most a `Vec` can hold, whatever the limit.
+    pub fn try_permutation(&self, max_bytes: u64) -> Result<Vec<u64>, MaterializeError> {
+        let bytes = self.materialized_bytes();
+        if bytes > max_bytes || isize::try_from(bytes).is_err() {
+            return Err(MaterializeError { bytes, max_bytes });
+        }
+
+        Ok(self.permutation())
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Like [`permutation`](Self::permutation), but fills the output in chunks on the rayon thread pool.
+    ///
+    /// # Panics
+    /// if the range doesn't fit in memory addressable by `usize`.
+    pub fn permutation_par(&self) -> Vec<u64>
+    where
+        F: Sync,
+    {
+        use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+        use rayon::slice::ParallelSliceMut;
+
+        let mut out = vec![0; materialized_len(self.range)];
+        out.par_chunks_mut(PAR_CHUNK_LEN)
+            .enumerate()
+            .for_each(|(i, chunk)| self.shuffle_chunk((i * PAR_CHUNK_LEN) as u64, chunk));
+        out
+    }
+
+    #[cfg(feature = "rayon")]
+    /// `shuffle(0)`, `shuffle(1)`, ... up to `range` as a rayon parallel iterator,
+    /// splitting `0..range` across the thread pool. Collecting into a `Vec` keeps the sequential order.
+    pub fn par_shuffle(&self) -> impl rayon::iter::ParallelIterator<Item = u64> + '_
+    where
+        F: Sync,
+    {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+        (0..self.range).into_par_iter().map(|m| self.shuffle(m))
+    }
+
+    // `shuffle` of `LANES` values at once: every round runs over all lanes before the next one starts,
+    // so the independent round function calls sit side by side, only the cycle walk is per value
+    #[inline]
+    fn shuffle_lanes(&self, m: [u64; LANES]) -> [u64; LANES] {
+        self.split().encrypt_lanes(m, self.rounds, |j, right| self.round(j, right)).map(|mut c| {
+            while c >= self.range {
+                c = self.encrypt(c);
+            }
+            c
+        })
+    }
+
+    /// Shuffle every value of `input` into `out`, `out[i] == shuffle(input[i])`.
+    ///
+    /// Values are processed in batches of 8, running each Feistel round over the whole batch,
+    /// so the round function calls of neighbouring values are independent and can overlap.
+    ///
+    /// # Panics
+    /// if `input` and `out` have different lengths.
+    pub fn shuffle_into(&self, input: &[u64], out: &mut [u64]) {
+        assert_eq!(input.len(), out.len(), "input and output must have the same length");
+
+        let (mut input_chunks, mut out_chunks) = (input.chunks_exact(LANES), out.chunks_exact_mut(LANES));
+        for (m, c) in input_chunks.by_ref().zip(out_chunks.by_ref()) {
+            c.copy_from_slice(&self.shuffle_lanes(m.try_into().unwrap()));
+        }
+        for (&m, c) in input_chunks.remainder().iter().zip(out_chunks.into_remainder()) {
+            *c = self.shuffle(m);
+        }
+    }
+
+    /// Shuffle every value of `values` in place, see [`shuffle_into`](Self::shuffle_into).
+    pub fn shuffle_many(&self, values: &mut [u64]) {
+        let mut chunks = values.chunks_exact_mut(LANES);
+        for m in chunks.by_ref() {
+            m.copy_from_slice(&self.shuffle_lanes(m[..].try_into().unwrap()));
+        }
+        for m in chunks.into_remainder() {
+            *m = self.shuffle(*m);
+        }
+    }
+
+    /// Reorder `buf` in place so that the element at position `i` moves to position `shuffle(i)`,
+    /// e.g. to emit a list of candidates that isn't a contiguous range in the same order.
+    ///
+    /// Follows the cycles of the permutation with swaps, the only allocation is a bitset of `range` bits.
+    ///
+    /// # Panics
+    /// if the length of `buf` isn't the range.
+    pub fn shuffle_order<T>(&self, buf: &mut [T]) {
+        assert_eq!(buf.len() as u64, self.range, "buffer length must equal the range");
+
+        let mut visited = vec![0u64; buf.len().div_ceil(64)];
+        for start in 0..buf.len() {
+            if visited[start / 64] & 1 << (start % 64) != 0 {
+                continue;
+            }
+            visited[start / 64] |= 1 << (start % 64);
+
+            // `buf[start]` always holds the element still waiting for its spot
+            let mut next = self.shuffle(start as u64) as usize;
+            while next != start {
+                buf.swap(start, next);
+                visited[next / 64] |= 1 << (next % 64);
+                next = self.shuffle(next as u64) as usize;
+            }
+        }
+    }
+
+    #[inline]
+    fn shuffle_chunk(&self, start: u64, chunk: &mut [u64]) {
+        for (m, out) in (start..).zip(chunk) {
+            *out = self.shuffle(m);
+        }
+    }
+
+    /// Turn this generator into a closure yielding `shuffle(0)`, `shuffle(1)`, ...
+    /// up to `range`, and `None` afterwards.
+    ///
+    /// ```
+    /// # use blackrock2::generator::BlackRockGenerator;
+    /// let values = core::iter::from_fn(BlackRockGenerator::with_seed(10, 1).into_fn());
+    /// assert_eq!(values.count(), 10);
+    /// ```
+    pub fn into_fn(self) -> impl FnMut() -> Option<u64> {
+        let mut range = 0..self.range;
+        move || range.next().map(|x| self.shuffle(x))
+    }
+}
+
+impl BlackRockGenerator {
+    /// An iterator over `shuffle(0), shuffle(1), ..` for the whole range, built from this generator itself,
+    /// so it can't end up with a different seed or round count than the one used elsewhere.
+    pub fn shuffled_range(self) -> BlackRockIter {
+        BlackRockIter { range: 0..self.range, generator: self, offset: 0 }
+    }
+
+    /// Swap the directions of this permutation, the returned generator's `shuffle` is this one's `unshuffle`,
+    /// e.g. to decode response-side values with the same API used to encode them.
+    pub const fn inverted(self) -> InverseGenerator {
+        InverseGenerator { generator: self }
+    }
+
+    /// Chain this permutation with `other`, the returned generator's `shuffle` is `other.shuffle(self.shuffle(m))`,
+    /// e.g. for layered obfuscation with independently keyed generators.
+    ///
+    /// # Panics
+    /// if the two generators have different ranges.
+    pub fn then(self, other: BlackRockGenerator) -> ComposedGenerator {
+        assert_eq!(self.range, other.range, "can only compose generators over the same range");
+        ComposedGenerator { first: self, second: other }
+    }
+
+    /// Precompute the padded power of two domain `encrypt` permutes, so that outputs can be
+    /// produced by filtering a single pass over it instead of cycle walking every value.
+    ///
+    /// The padded generator produces the same set of values as [`shuffle`](Self::shuffle) over the range,
+    /// but in a different order, see [`PaddedGenerator`].
+    pub const fn into_padded(self) -> PaddedGenerator {
+        PaddedGenerator {
+            max_domain: (self.b_mask << self.a_bits) | self.a_mask,
+            generator: self,
+        }
+    }
+}
+
+/// A permutation with fixed points, created by [`BlackRockGenerator::with_anchors`].
+#[derive(Debug, Clone)]
+#[must_use = "this generator does nothing unless driven"]
+pub struct AnchoredGenerator {
+    generator: BlackRockGenerator,
+    anchors: Vec<u64>,
+}
+
+impl AnchoredGenerator {
+    /// Anchors map to themselves, every other value is shuffled over the values that aren't anchors.
+    pub fn shuffle(&self, m: u64) -> u64 {
+        let below = self.anchors.partition_point(|&anchor| anchor < m);
+        if self.anchors.get(below) == Some(&m) {
+            return m;
+        }
+
+        // shuffle the rank among non-anchors, then find the value with that rank
+        let mut c = self.generator.shuffle(m - below as u64);
+        for &anchor in &self.anchors {
+            if anchor > c {
+                break;
+            }
+            c += 1;
+        }
+        c
+    }
+}
+
+/// The inverse of a permutation, created by [`BlackRockGenerator::inverted`].
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[must_use = "this generator does nothing unless driven"]
+pub struct InverseGenerator {
+    generator: BlackRockGenerator,
+}
+
+impl InverseGenerator {
+    /// [`BlackRockGenerator::unshuffle`] of the original generator, `c` must be less than the range.
+    pub fn shuffle(&self, c: u64) -> u64 {
+        self.generator.unshuffle(c)
+    }
+
+    /// [`BlackRockGenerator::shuffle`] of the original generator.
+    pub fn unshuffle(&self, m: u64) -> u64 {
+        self.generator.shuffle(m)
+    }
+
+    /// Get the original generator back.
+    // not const, with the `zeroize` feature dropping the wrapper can't be evaluated at compile time
+    pub fn inverted(self) -> BlackRockGenerator {
+        self.generator
+    }
+}
+
+/// Two permutations applied one after the other, created by [`BlackRockGenerator::then`].
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[must_use = "this generator does nothing unless driven"]
+pub struct ComposedGenerator {
+    first: BlackRockGenerator,
+    second: BlackRockGenerator,
+}
+
+impl ComposedGenerator {
+    /// The range of both generators.
+    pub const fn range(&self) -> u64 {
+        self.first.range
+    }
+
+    /// Shuffle `m` with the first generator, then the second, `m` must be less than the range.
+    pub fn shuffle(&self, m: u64) -> u64 {
+        self.second.shuffle(self.first.shuffle(m))
+    }
+
+    /// The inverse of [`shuffle`](Self::shuffle), unshuffles with the second generator, then the first.
+    pub fn unshuffle(&self, c: u64) -> u64 {
+        self.first.unshuffle(self.second.unshuffle(c))
+    }
+
+    /// Split the composition back into its two generators, in the order they are applied.
+    pub fn into_parts(self) -> (BlackRockGenerator, BlackRockGenerator) {
+        (self.first, self.second)
+    }
+}
+
+/// A generator over the whole padded Feistel domain, created by [`BlackRockGenerator::into_padded`].
+///
+/// Every index in the padded domain is encrypted once and outputs outside of the range are dropped,
+/// so there is no retry loop per value. That yields a permutation of `0..range` too,
+/// but it orders values by their index in the padded domain, not by cycle walked position,
+/// so it is **not** the order [`BlackRockGenerator::shuffle`] produces.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[must_use = "this generator does nothing unless driven"]
+pub struct PaddedGenerator {
+    generator: BlackRockGenerator,
+    max_domain: u64,
+}
+
+impl PaddedGenerator {
+    /// The largest index of the padded domain, the domain is `0..=max_domain`.
+    pub const fn max_domain(&self) -> u64 {
+        self.max_domain
+    }
+
+    /// Encrypt a single index of the padded domain without retrying,
+    /// `None` if it lands outside of the range.
+    ///
+    /// `m` must be at most [`max_domain`](Self::max_domain).
+    pub fn shuffle(&self, m: u64) -> Option<u64> {
+        debug_assert!(m <= self.max_domain, "{m} is outside of the padded domain 0..={}", self.max_domain);
+
+        let c = self.generator.encrypt(m);
+        (c < self.generator.range).then_some(c)
+    }
+
+    /// Every value of the range exactly once, in padded domain order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = u64> + FusedIterator + '_ {
+        // an empty range still has a domain of one value, which is filtered out
+        (0..=self.max_domain).filter_map(|m| self.shuffle(m))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn verify(range: u64, seed: u64, rounds: usize) {
+        verify_generator(&BlackRockGenerator::with_seed_and_rounds(range, seed, rounds));
+    }
+
+    fn verify_generator<F: RoundFunction + core::fmt::Debug>(randomizer: &BlackRockGenerator<F>) {
+        let range = randomizer.range;
+        println!("randomizer: {randomizer:?}");
+
+        // make sure every number gets added exactly once
+        let mut list = vec![0; range as usize];
+        for i in 0..range {
+            let x = randomizer.shuffle(i) as usize;
+            list[x] += 1;
+        }
+
+        for (i, number) in list.into_iter().enumerate() {
+            assert_eq!(number, 1, "Index: {i}, range: {range:?}");
+        }
+    }
+
+    #[test]
+    fn verify_ranges() {
+        let mut range = 3015 * 3;
+
+        for i in 0..5 {
+            range += 11 + i;
+            range *= 1 + i;
+
+            verify(range, 0, 6);
+        }
+
+        verify(10, 0, 3);
+        verify(100, 0, 3);
+    }
+
+    #[test]
+    fn verify_power_of_two_edges() {
+        for bits in 1..=16 {
+            for range in [(1 << bits) - 1, 1 << bits, (1 << bits) + 1] {
+                verify(range, 7, 3);
+                verify(range, 7, 4);
+            }
+        }
+    }
+
+    #[test]
+    fn huge_ranges() {
+        let ranges = [(1 << 40) - 1, 1 << 40, (1 << 40) + 1, 3 << 40, (1 << 63) + 1, u64::MAX - 1, u64::MAX];
+        for range in ranges {
+            let randomizer = BlackRockGenerator::with_seed(range, 7);
+            assert!(randomizer.domain() >= u128::from(range) && randomizer.domain() <= 1 << 64);
+
+            let samples = (0..1000).chain(range - 1000..range).chain((1..1000).map(|i| range / 1000 * i));
+            let mut seen = HashSet::new();
+            for m in samples {
+                let c = randomizer.shuffle(m);
+                assert!(c < range);
+                assert_eq!(randomizer.unshuffle(c), m);
+                seen.insert((m, c));
+            }
+            assert_eq!(seen.iter().map(|&(_, c)| c).collect::<HashSet<_>>().len(), seen.len());
+        }
+    }
+
+    #[test]
+    fn round_overflow() {
+        // a round output near u64::MAX used to overflow the addition in debug builds
+        #[derive(Debug)]
+        struct Max;
+        impl RoundFunction for Max {
+            fn mix(&self, j: u64, right: u64, _: u64) -> u64 {
+                u64::MAX - j - right
+            }
+        }
+
+        for range in [10, 1000, 65_537] {
+            verify_generator(&BlackRockGenerator::with_round_function(range, 7, 3, Max));
+        }
+        assert!(BlackRockGenerator::with_round_function(u64::MAX, 7, 3, Max).shuffle(12345) < u64::MAX);
+    }
+
+    // runs under `--no-default-features` too, the cipher itself needs neither `std` nor `rand`.
+    #[test]
+    fn seeded_core() {
+        let randomizer = BlackRockGenerator::with_seed_and_rounds(1000, 0x5eed, 3);
+        verify_generator(&randomizer);
+
+        for i in [0, 1, 500, 999] {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+
+        let mut iter = crate::BlackRockIter::with_seed_and_rounds(1000, 0x5eed, 3);
+        assert_eq!(iter.next(), Some(randomizer.shuffle(0)));
+        assert_eq!(iter.next_back(), Some(randomizer.shuffle(999)));
+    }
+
+    #[test]
+    fn custom_round_function() {
+        #[derive(Debug)]
+        struct Trivial;
+
+        impl RoundFunction for Trivial {
+            fn mix(&self, j: u64, right: u64, seed: u64) -> u64 {
+                right ^ seed ^ j
+            }
+        }
+
+        for range in [0, 1, 10, 1000, 65_537] {
+            let randomizer = BlackRockGenerator::with_round_function(range, 5, 3, Trivial);
+            verify_generator(&randomizer);
+            assert!((0..range).all(|m| randomizer.unshuffle(randomizer.shuffle(m)) == m));
+        }
+
+        // the default is the same sip based function as before
+        let sip = BlackRockGenerator::with_round_function(1000, 5, 3, SipRound);
+        let default = BlackRockGenerator::with_seed_and_rounds(1000, 5, 3);
+        assert!((0..1000).all(|m| sip.shuffle(m) == default.shuffle(m)));
+    }
+
+    #[test]
+    fn dont_get_stuck() {
+        for range in [10, 100] {
+            for seed in 0..100 {
+                let randomizer = BlackRockGenerator::with_seed_and_rounds(range, seed, 3);
+
+                for i in 0..range {
+                    let _ = randomizer.shuffle(i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn into_fn_covers_range() {
+        let mut next = BlackRockGenerator::with_seed(100, 7).into_fn();
+
+        let mut seen = vec![false; 100];
+        while let Some(x) = next() {
+            assert!(!std::mem::replace(&mut seen[x as usize], true), "Duplicate: {x}");
+        }
+
+        assert!(seen.into_iter().all(|x| x));
+        assert_eq!(next(), None);
+    }
+
+    #[test]
+    fn estimated_duration() {
+        let ipv4 = BlackRockGenerator::with_seed(1 << 32, 0);
+        assert_eq!(ipv4.estimated_duration(1024.0), Duration::from_secs(1 << 22));
+        assert_eq!(ipv4.estimated_duration(1024.0 * 1024.0 * 1024.0), Duration::from_secs(4));
+
+        let small = BlackRockGenerator::with_seed(36_000_000, 0);
+        assert_eq!(small.estimated_duration(10_000.0), Duration::from_secs(3600));
+        assert_eq!(small.estimated_duration(3.0 * 10_000.0), Duration::from_secs(1200));
+
+        assert_eq!(small.estimated_duration(0.0), Duration::MAX);
+        assert_eq!(small.estimated_duration(-1.0), Duration::MAX);
+        assert_eq!(small.estimated_duration(f64::NAN), Duration::MAX);
+        assert_eq!(small.estimated_duration(f64::MIN_POSITIVE), Duration::MAX);
+    }
+
+    #[test]
+    fn high_bit_histogram() {
+        let randomizer = BlackRockGenerator::with_seed(1 << 16, 3);
+        assert_eq!(randomizer.high_bit_histogram(0), [1 << 16]);
+        assert_eq!(randomizer.high_bit_histogram(4), [1 << 12; 16]);
+
+        let randomizer = BlackRockGenerator::with_seed(1000, 3);
+        let buckets = randomizer.high_bit_histogram(2);
+        assert_eq!(buckets, [256, 256, 256, 232]);
+
+        // the first outputs already cover every bucket about evenly, 256 each
+        let near_uniform = |buckets: Vec<u64>| buckets.iter().all(|&count| count.abs_diff(256) < 80);
+        let randomizer = BlackRockGenerator::with_seed(1 << 16, 3);
+        assert_eq!(randomizer.prefix_high_bit_histogram(4, 4096).iter().sum::<u64>(), 4096);
+        assert!(near_uniform(randomizer.prefix_high_bit_histogram(4, 4096)));
+
+        // the identity fills the buckets one after the other
+        let identity = BlackRockGenerator::with_seed_and_rounds(1 << 16, 3, 0);
+        assert_eq!(identity.high_bit_histogram(4), [1 << 12; 16]);
+        assert!(!near_uniform(identity.prefix_high_bit_histogram(4, 4096)));
+    }
+
+    #[test]
+    #[should_panic = "buckets don't fit in memory"]
+    fn high_bit_histogram_too_many_buckets() {
+        BlackRockGenerator::with_seed(u64::MAX, 1).high_bit_histogram(64);
+    }
+
+    #[test]
+    fn auto_rounds() {
+        let rounds = |range| BlackRockGenerator::with_auto_rounds(range, 0).rounds;
+
+        let ranges = [0, 1, 100, 1 << 14, 1 << 16, 1 << 24, 1 << 32, 1 << 48, u64::MAX];
+        assert!(ranges.windows(2).all(|w| rounds(w[0]) <= rounds(w[1])));
+        assert_eq!(rounds(100), 3);
+        assert_eq!(rounds(1 << 32), 5);
+        assert_eq!(rounds(u64::MAX), 7);
+
+        let range = 1 << 16;
+        let rounds = rounds(range);
+        assert!(rounds > 3);
+        verify(range, 0, rounds);
+    }
+
+    #[test]
+    fn degenerate() {
+        assert!(!BlackRockGenerator::with_seed(0, 0).is_degenerate(0.0));
+        assert!(BlackRockGenerator::with_seed_and_rounds(3, 0, 0).is_degenerate(0.5));
+
+        // range 2 is either the identity or a swap
+        let (identity, swap): (Vec<_>, Vec<_>) = (0..64)
+            .map(|seed| BlackRockGenerator::with_seed(2, seed))
+            .partition(|randomizer| randomizer.shuffle(0) == 0);
+
+        assert!(!identity.is_empty() && !swap.is_empty());
+        assert!(identity.iter().all(|randomizer| randomizer.is_degenerate(0.5)));
+        assert!(swap.iter().all(|randomizer| !randomizer.is_degenerate(0.0)));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_permutation() {
+        for range in [0, 1, 7, 100_003] {
+            let randomizer = BlackRockGenerator::with_seed(range, 11);
+
+            let sequential = randomizer.permutation();
+            assert_eq!(sequential.len() as u64, range);
+            assert!(sequential.iter().enumerate().all(|(i, &x)| randomizer.shuffle(i as u64) == x));
+            assert_eq!(randomizer.permutation_par(), sequential);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_shuffle() {
+        use rayon::iter::ParallelIterator;
+
+        for range in [0, 1, 7, 100_003] {
+            let randomizer = BlackRockGenerator::with_seed(range, 5);
+            let mut values = randomizer.par_shuffle().collect::<Vec<_>>();
+            assert_eq!(values, randomizer.permutation());
+
+            values.sort_unstable();
+            assert_eq!(values, (0..range).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn rebuild() {
+        let mut randomizer = BlackRockGenerator::with_seed(100, 5);
+        randomizer.range = 5000;
+        randomizer.rebuild();
+
+        let fresh = BlackRockGenerator::with_seed(5000, 5);
+        assert_eq!((randomizer.a_bits, randomizer.a_mask, randomizer.b_mask), (fresh.a_bits, fresh.a_mask, fresh.b_mask));
+        verify_generator(&randomizer);
+    }
+
+    #[test]
+    fn anchors() {
+        let range = 1000;
+        let anchors = [999, 0, 500, 501, 10, 10];
+        let randomizer = BlackRockGenerator::with_anchors(range, 4, 3, &anchors);
+
+        let mut seen = vec![false; range as usize];
+        let mut moved = 0;
+        for m in 0..range {
+            let c = randomizer.shuffle(m);
+            assert!(!std::mem::replace(&mut seen[c as usize], true), "Duplicate: {c}");
+
+            if anchors.contains(&m) {
+                assert_eq!(c, m);
+            } else {
+                assert!(!anchors.contains(&c));
+                moved += usize::from(c != m);
+            }
+        }
+        assert!(moved > 900);
+
+        let all = (0..10).collect::<Vec<_>>();
+        let randomizer = BlackRockGenerator::with_anchors(10, 4, 3, &all);
+        assert!((0..10).all(|m| randomizer.shuffle(m) == m));
+    }
+
+    #[test]
+    fn find_seed() {
+        let seed = BlackRockGenerator::find_seed_for(100, 3, &[(5, 42)], 10_000).unwrap();
+        assert_eq!(BlackRockGenerator::with_seed(100, seed).shuffle(5), 42);
+
+        let constraints = [(0, 1), (1, 0)];
+        let seed = BlackRockGenerator::find_seed_for(4, 3, &constraints, 10_000).unwrap();
+        let randomizer = BlackRockGenerator::with_seed(4, seed);
+        assert!(constraints.iter().all(|&(index, output)| randomizer.shuffle(index) == output));
+
+        assert_eq!(BlackRockGenerator::find_seed_for(100, 3, &[(5, 100)], 10_000), None);
+        assert_eq!(BlackRockGenerator::find_seed_for(100, 3, &[(5, 42)], 0), None);
+        assert_eq!(BlackRockGenerator::find_seed_for(100, 3, &[(5, 42), (6, 42)], 1000), None);
+    }
+
+    #[test]
+    fn consecutive_hamming() {
+        for (range, bits) in [(1 << 16, 16.0), (1 << 32, 32.0), (1_000_000, 20.0)] {
+            let distance = BlackRockGenerator::with_seed(range, 21).avg_consecutive_hamming(10_000);
+            assert!((distance - bits / 2.0).abs() < 1.0, "range {range}: {distance}");
+        }
+
+        let identity = BlackRockGenerator::with_seed_and_rounds(1 << 16, 0, 0);
+        assert!(identity.avg_consecutive_hamming(10_000) < 3.0);
+
+        assert_eq!(BlackRockGenerator::with_seed(1, 0).avg_consecutive_hamming(100), 0.0);
+        assert_eq!(BlackRockGenerator::with_seed(100, 0).avg_consecutive_hamming(0), 0.0);
+    }
+
+    #[test]
+    fn deterministic() {
+        let a = BlackRockGenerator::deterministic(100_000);
+        let b = BlackRockGenerator::deterministic(100_000);
+        assert_eq!(a.config(), (100_000, DETERMINISTIC_SEED, 3));
+        assert!((0..100_000).all(|m| a.shuffle(m) == b.shuffle(m)));
+
+        // pinned, the order must not change between crate versions
+        assert_eq!((0..4).map(|m| a.shuffle(m)).collect::<Vec<_>>(), [89282, 66725, 44593, 66202]);
+    }
+
+    #[test]
+    fn tenants() {
+        let order = |tenant| {
+            let randomizer = BlackRockGenerator::for_tenant(1000, 0xfeed, tenant, 3);
+            (0..1000).map(|i| randomizer.shuffle(i)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(order(1), order(1));
+        assert_ne!(order(1), order(2));
+
+        // unrelated orders agree on about one position in a thousand
+        let same = order(1).into_iter().zip(order(2)).filter(|(a, b)| a == b).count();
+        assert!(same < 10, "{same} positions in common");
+
+        let other_master = BlackRockGenerator::for_tenant(1000, 0xbeef, 1, 3);
+        assert_ne!((0..1000).map(|i| other_master.shuffle(i)).collect::<Vec<_>>(), order(1));
+        verify_generator(&other_master);
+    }
+
+    #[test]
+    fn round_state() {
+        let randomizer = BlackRockGenerator::with_seed(1000, 42);
+        assert_eq!(randomizer.debug_round_state(1, 0x1234), (1, 0x1234, 42, 0xf3016d19bc9ad940));
+
+        // four siprounds over that state, computed independently of this crate
+        assert_eq!(randomizer.debug_round_state(2, 7), (2, 7, 42, 0xf3016d19bc9ad940));
+        assert_eq!(randomizer.round(2, 7), 0xe4c30f84cc4deb35);
+    }
+
+    #[test]
+    fn cached_seed_state() {
+        for seed in [0, 1, 42, u64::MAX] {
+            for (j, right) in [(1, 0), (2, 7), (3, u64::MAX), (u64::MAX, 0x1234)] {
+                assert_eq!(sip_with_seed_state(j, right, sip_seed_state(seed)), sip(j, right, seed));
+            }
+        }
+
+        // the uncached path through `mix` gives the same permutation
+        struct Uncached;
+        impl RoundFunction for Uncached {
+            fn mix(&self, j: u64, right: u64, seed: u64) -> u64 {
+                sip(j, right, seed)
+            }
+        }
+
+        let cached = BlackRockGenerator::with_seed(100_003, 9);
+        let uncached = BlackRockGenerator::with_round_function(100_003, 9, 3, Uncached);
+
+        let expected = (0..100_003).map(|m| uncached.shuffle(m)).collect::<Vec<_>>();
+        let actual = cached.permutation();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn unshuffle() {
+        for (range, rounds) in [(1000, 3), (1000, 4), (4099, 5)] {
+            let randomizer = BlackRockGenerator::with_seed_and_rounds(range, 17, rounds);
+            assert!((0..range).all(|m| randomizer.unshuffle(randomizer.shuffle(m)) == m));
+        }
+
+        // both final swap parities, powers of two and just past them
+        for range in [1, 2, 3, 64, 65, 1 << 12, (1 << 12) + 1, 10_007] {
+            for seed in [0, 1, u64::MAX] {
+                for rounds in 0..=6 {
+                    let randomizer = BlackRockGenerator::with_seed_and_rounds(range, seed, rounds);
+                    assert!(
+                        (0..range).all(|m| randomizer.unshuffle(randomizer.shuffle(m)) == m),
+                        "range {range}, seed {seed}, rounds {rounds}"
+                    );
+
+                    let padded = randomizer.into_padded();
+                    assert!((0..=padded.max_domain()).all(|m| padded.generator.decrypt(padded.generator.encrypt(m)) == m));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let randomizer = BlackRockGenerator::with_seed_and_rounds(123_456, 0xdead_beef, 5);
+        let bytes = randomizer.to_bytes();
+        assert_eq!(bytes.len(), BlackRockGenerator::ENCODED_LEN);
+
+        let decoded = BlackRockGenerator::from_bytes(&bytes).unwrap();
+        assert_eq!((decoded.range, decoded.seed, decoded.rounds), (123_456, 0xdead_beef, 5));
+        assert!((0..1000).all(|i| decoded.shuffle(i) == randomizer.shuffle(i)));
+    }
+
+    #[test]
+    fn bytes_rejects_corruption() {
+        let bytes = BlackRockGenerator::with_seed(1000, 42).to_bytes();
+
+        for i in 0..bytes.len() {
+            let mut corrupt = bytes.clone();
+            corrupt[i] ^= 0x10;
+            assert_eq!(BlackRockGenerator::from_bytes(&corrupt).unwrap_err(), FromBytesError::ChecksumMismatch);
+        }
+
+        assert_eq!(BlackRockGenerator::from_bytes(&bytes[1..]).unwrap_err(), FromBytesError::InvalidLength(bytes.len() - 1));
+        assert_eq!(BlackRockGenerator::from_bytes(&[]).unwrap_err(), FromBytesError::InvalidLength(0));
+
+        let mut future = bytes.clone();
+        future[0] = 2;
+        let checksum = BlackRockGenerator::checksum(&future[..future.len() - 8]);
+        future.truncate(future.len() - 8);
+        future.extend_from_slice(&checksum.to_le_bytes());
+        assert_eq!(BlackRockGenerator::from_bytes(&future).unwrap_err(), FromBytesError::UnsupportedVersion(2));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn common_prefix() {
+        for (range_a, range_b, k) in [(100, 100, 30), (100, 50, 40), (10, 100, 100), (100, 100, 0)] {
+            let a = BlackRockGenerator::with_seed(range_a, 1);
+            let b = BlackRockGenerator::with_seed(range_b, 2);
+
+            let prefix_b = (0..k.min(range_b)).map(|i| b.shuffle(i)).collect::<Vec<_>>();
+            let expected = (0..k.min(range_a))
+                .map(|i| a.shuffle(i))
+                .filter(|x| prefix_b.contains(x))
+                .collect::<Vec<_>>();
+
+            assert_eq!(a.common_prefix_targets(&b, k), expected);
+        }
+
+        let a = BlackRockGenerator::with_seed(100, 1);
+        assert_eq!(a.common_prefix_targets(&a, 30), (0..30).map(|i| a.shuffle(i)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn permutation_order() {
+        assert_eq!(BlackRockGenerator::with_seed(0, 1).permutation_order(), 1);
+        assert_eq!(BlackRockGenerator::with_seed_and_rounds(50, 1, 0).permutation_order(), 1);
+
+        for seed in 0..8 {
+            let randomizer = BlackRockGenerator::with_seed(60, seed);
+            let order = randomizer.permutation_order();
+
+            let apply = |n: u64, x: u64| (0..n).fold(x, |x, _| randomizer.shuffle(x));
+            assert!((0..60).all(|x| apply(order, x) == x), "seed {seed}");
+
+            // no proper divisor of the order brings everything back
+            for p in (2..=order).filter(|p| order % p == 0 && (2..*p).all(|d| p % d != 0)) {
+                assert!((0..60).any(|x| apply(order / p, x) != x), "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn target_count() {
+        for count in [0, 1, 77, 4096] {
+            let randomizer = BlackRockGenerator::for_target_count(count, 3, 3);
+            assert_eq!(randomizer.range(), count);
+            verify_generator(&randomizer);
+        }
+    }
+
+    #[test]
+    fn keys() {
+        let seed = |randomizer: BlackRockGenerator| randomizer.seed;
+
+        let a = seed(BlackRockGenerator::with_key(100, b"scan-1", 3));
+        assert_eq!(a, seed(BlackRockGenerator::with_key(100, b"scan-1", 3)));
+        assert_ne!(a, seed(BlackRockGenerator::with_key(100, b"scan-2", 3)));
+        assert_ne!(seed(BlackRockGenerator::with_key(100, b"", 3)), seed(BlackRockGenerator::with_key(100, b"\0", 3)));
+        verify_generator(&BlackRockGenerator::with_key(100, b"scan-1", 3));
+
+        let hasher = |key: &[u8]| key.iter().fold(7u64, |h, &b| h.wrapping_mul(31).wrapping_add(u64::from(b)));
+        let custom = seed(BlackRockGenerator::with_key_and_hasher(100, b"scan-1", 3, hasher));
+        assert_eq!(custom, hasher(b"scan-1"));
+        assert_ne!(custom, a);
+        assert_eq!(custom, seed(BlackRockGenerator::with_key_and_hasher(100, b"scan-1", 3, hasher)));
+    }
+
+    #[test]
+    fn padded() {
+        for (range, rounds) in [(0, 3), (1, 3), (1000, 3), (1000, 4), (65_537, 5)] {
+            let padded = BlackRockGenerator::with_seed_and_rounds(range, 9, rounds).into_padded();
+            assert!(padded.max_domain() >= range.saturating_sub(1));
+
+            let mut values = padded.iter().collect::<Vec<_>>();
+            values.sort_unstable();
+            assert_eq!(values, (0..range).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn chi_square() {
+        assert_eq!(BlackRockGenerator::with_seed(0, 1).chi_square_uniformity(10), 0.0);
+
+        // 81 degrees of freedom, the critical value at p = 0.001 is about 124.
+        // auto rounds (4 here) stay below it, the default 3 rounds land around 500 to 1350 at this size
+        for seed in 0..4 {
+            let statistic = BlackRockGenerator::with_auto_rounds(100_000, seed).chi_square_uniformity(10);
+            assert!(statistic < 124.0, "seed {seed}: {statistic}");
+        }
+
+        let identity = BlackRockGenerator::with_seed_and_rounds(100_000, 1, 0).chi_square_uniformity(10);
+        assert!(identity > 100_000.0, "{identity}");
+    }
+
+    #[test]
+    fn retries() {
+        assert_eq!(BlackRockGenerator::with_seed(0, 1).total_retries(), 0);
+
+        // just past a power of two wastes most of the domain
+        for range in [17, 257, 1025, 1 << 16] {
+            let randomizer = BlackRockGenerator::with_seed(range, 1);
+
+            let mut tally = 0;
+            for m in 0..range {
+                let mut c = randomizer.encrypt(m);
+                while c >= range {
+                    tally += 1;
+                    c = randomizer.encrypt(c);
+                }
+            }
+
+            assert_eq!(randomizer.total_retries(), tally);
+            assert!(tally <= (randomizer.domain() - u128::from(range)) as u64);
+        }
+
+        // the IPv4 range sits in a 2^33 domain
+        let randomizer = BlackRockGenerator::with_seed(1 << 32, 1);
+        assert_eq!(randomizer.retry_probability(), 0.5);
+        assert_eq!(randomizer.total_retries(), 1 << 32);
+
+        let randomizer = BlackRockGenerator::with_seed((1 << 32) + 1, 1);
+        let p = randomizer.retry_probability();
+        let estimate = ((1u64 << 32) + 1) as f64 * p / (1.0 - p);
+        assert!((randomizer.total_retries() as f64 - estimate).abs() / estimate < 1e-6);
+
+        assert_eq!(BlackRockGenerator::with_seed(u64::MAX, 1).total_retries(), 1);
+    }
+
+    #[test]
+    fn shuffle_const() {
+        for range in [0, 1, 10, 1000, 65_537] {
+            let three = BlackRockGenerator::with_seed_and_rounds(range, 11, 3);
+            let four = BlackRockGenerator::with_seed_and_rounds(range, 11, 4);
+            for m in 0..range.min(5000) {
+                assert_eq!(three.shuffle_const::<3>(m), three.shuffle(m));
+                assert_eq!(three.shuffle_const::<4>(m), four.shuffle(m));
+            }
+        }
+    }
+
+    #[test]
+    fn describe_split() {
+        assert_eq!(
+            BlackRockGenerator::with_seed(1000, 1).describe_split(),
+            "range=1000 → a=32 (5 bits) × b=32 (5 bits) → domain=1024, retry_prob=2.3%",
+        );
+
+        let description = BlackRockGenerator::with_seed(u64::MAX, 1).describe_split();
+        assert!(description.contains("a=4294967296 (32 bits) × b=4294967296 (32 bits) → domain=18446744073709551616"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn verify_against() {
+        let randomizer = BlackRockGenerator::with_seed(1000, 3);
+        let mut reference = String::from("# index,expected\n\n");
+        for i in [0, 1, 500, 999] {
+            reference += &format!("{i}, {}\n", randomizer.shuffle(i));
+        }
+        assert!(randomizer.verify_against(reference.as_bytes()).is_ok());
+
+        let bad = format!("{reference}7,{}\n", randomizer.shuffle(7) + 1);
+        match randomizer.verify_against(bad.as_bytes()) {
+            Err(VerifyError::Mismatch { line: 7, index: 7, expected, actual }) => {
+                assert_eq!(actual, randomizer.shuffle(7));
+                assert_eq!(expected, actual + 1);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        assert!(matches!(randomizer.verify_against("1;2".as_bytes()), Err(VerifyError::Parse { line: 1 })));
+        assert!(matches!(randomizer.verify_against("1000,0".as_bytes()), Err(VerifyError::OutOfRange { line: 1, index: 1000 })));
+    }
+
+    #[test]
+    fn disjoint_samples() {
+        let randomizer = BlackRockGenerator::with_seed(1000, 8);
+        let samples = randomizer.disjoint_samples(7, 100).unwrap();
+
+        assert_eq!(samples.len(), 7);
+        assert!(samples.iter().all(|sample| sample.len() == 100));
+
+        let all = samples.concat().into_iter().collect::<HashSet<_>>();
+        assert_eq!(all.len(), 700);
+        assert!(all.iter().all(|&x| x < 1000));
+
+        assert_eq!(randomizer.disjoint_samples(10, 100).unwrap().concat(), randomizer.permutation());
+        assert_eq!(randomizer.disjoint_samples(0, 5000), Some(Vec::new()));
+
+        assert_eq!(randomizer.disjoint_samples(11, 100), None);
+        assert_eq!(randomizer.disjoint_samples(usize::MAX, u64::MAX), None);
+    }
+
+    #[test]
+    fn shuffle_with_steps() {
+        for range in [1, 1000, 1025, 65_537] {
+            let randomizer = BlackRockGenerator::with_seed(range, 6);
+            for m in 0..range.min(5000) {
+                let (c, steps) = randomizer.shuffle_with_steps(m);
+                assert_eq!(c, randomizer.shuffle(m));
+                assert!(steps >= 1);
+            }
+
+            let max = randomizer.max_walk_over(0..range.min(5000));
+            assert!(max >= 1);
+            assert_eq!(max, (0..range.min(5000)).map(|m| randomizer.shuffle_with_steps(m).1).max().unwrap());
+        }
+
+        // a range just past a power of two pads the domain the most, so it walks further
+        let padded = BlackRockGenerator::with_seed(1025, 6);
+        assert!(padded.max_walk_over(0..1025) > 1);
+        assert_eq!(padded.max_walk_over([]), 0);
+    }
+
+    #[test]
+    fn shuffled_range() {
+        let randomizer = BlackRockGenerator::with_seed_and_rounds(1000, 4, 5);
+        let manual = (0..1000).map(|m| randomizer.shuffle(m)).collect::<Vec<_>>();
+        assert_eq!(Clone::clone(&randomizer).shuffled_range().collect::<Vec<_>>(), manual);
+
+        let mut looped = Vec::new();
+        for c in randomizer {
+            looped.push(c);
+        }
+        assert_eq!(looped, manual);
+    }
+
+    #[test]
+    fn tiny_ranges() {
+        for range in 1..=8 {
+            for seed in [0, 1, 5, 0x5eed, u64::MAX] {
+                for rounds in 0..=6 {
+                    let randomizer = BlackRockGenerator::with_seed_and_rounds(range, seed, rounds);
+                    assert!(randomizer.is_permutation(), "range {range}, seed {seed}, rounds {rounds}");
+                    assert!((0..range).all(|m| randomizer.unshuffle(randomizer.shuffle(m)) == m));
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let randomizer = BlackRockGenerator::with_seed_and_rounds(1_000_000, 0x5eed, 5);
+        let json = serde_json::to_string(&randomizer).unwrap();
+        assert_eq!(json, r#"{"range":1000000,"seed":24301,"rounds":5}"#);
+
+        let decoded = serde_json::from_str::<BlackRockGenerator>(&json).unwrap();
+        assert_eq!(decoded.config(), randomizer.config());
+        assert!((0..10_000).all(|m| decoded.shuffle(m) == randomizer.shuffle(m)));
+
+        // the split is rebuilt from the range, never read
+        assert_eq!((decoded.a_bits, decoded.a_mask, decoded.b_mask), (randomizer.a_bits, randomizer.a_mask, randomizer.b_mask));
+        assert_eq!(decoded.seed_state, randomizer.seed_state);
+        assert!(serde_json::from_str::<BlackRockGenerator>(r#"{"range":10,"seed":1,"rounds":3,"a_mask":0}"#).unwrap().is_permutation());
+
+        #[derive(Debug, Default)]
+        struct Trivial;
+        impl RoundFunction for Trivial {
+            fn mix(&self, j: u64, right: u64, seed: u64) -> u64 {
+                right ^ seed ^ j
+            }
+        }
+
+        let custom = serde_json::from_str::<BlackRockGenerator<Trivial>>(&json).unwrap();
+        assert_eq!(custom.config(), randomizer.config());
+        assert!((0..10_000).all(|m| custom.shuffle(m) == BlackRockGenerator::with_round_function(1_000_000, 0x5eed, 5, Trivial).shuffle(m)));
+    }
+
+    #[test]
+    fn debug() {
+        let randomizer = BlackRockGenerator::with_seed_and_rounds(1000, 5, 4);
+        assert_eq!(format!("{randomizer:?}"), "BlackRockGenerator { range: 1000, seed: 5, rounds: 4, round_fn: SipRound, .. }");
+    }
+
+    #[test]
+    fn zero_rounds_is_identity() {
+        for range in [0, 1, 2, 3, 100, 4099, 1 << 20] {
+            let randomizer = BlackRockGenerator::with_seed_and_rounds(range, 5, 0);
+            assert!((0..range.min(10_000)).all(|m| randomizer.shuffle(m) == m && randomizer.unshuffle(m) == m));
+        }
+        for range in [u64::MAX, 1 << 63] {
+            let randomizer = BlackRockGenerator::with_seed_and_rounds(range, 5, 0);
+            assert!([0, 1, range / 2, range - 1].into_iter().all(|m| randomizer.shuffle(m) == m));
+        }
+    }
+
+    #[test]
+    fn is_permutation() {
+        for (range, rounds) in [(0, 3), (1, 3), (2, 1), (100, 3), (1000, 4), (4099, 0), (65_537, 6)] {
+            let randomizer = BlackRockGenerator::with_seed_and_rounds_verified(range, 5, rounds);
+            assert!(randomizer.is_permutation());
+        }
+
+        let mut broken = BlackRockGenerator::with_seed(100, 5);
+        broken.b_mask = 3;
+        assert!(!broken.is_permutation());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "generator over 100 with 3 rounds is not a permutation"]
+    fn debug_verified_broken() {
+        let mut broken = BlackRockGenerator::with_seed(100, 5);
+        broken.b_mask = 3;
+        let _ = broken.debug_verified();
+    }
+
+    #[test]
+    fn first_collision() {
+        for (range, rounds) in [(0, 3), (1, 3), (100, 3), (1000, 4), (4099, 0)] {
+            assert_eq!(BlackRockGenerator::with_seed_and_rounds(range, 5, rounds).first_collision(), None);
+        }
+
+        // even a constant round function gives a bijection, see the doc comment
+        #[derive(Debug)]
+        struct Constant;
+        impl RoundFunction for Constant {
+            fn mix(&self, _: u64, _: u64, _: u64) -> u64 {
+                0
+            }
+        }
+        assert_eq!(BlackRockGenerator::with_round_function(1000, 5, 3, Constant).first_collision(), None);
+
+        // so break the private split instead: a mask that is too narrow for the range folds part of it onto the rest
+        let mut broken = BlackRockGenerator::with_seed(100, 5);
+        broken.b_mask = 3;
+        let (i, j) = broken.first_collision().unwrap();
+        assert!(i < j);
+        assert_eq!(broken.shuffle(i), broken.shuffle(j));
+        assert!((0..j).filter(|&k| k != i).all(|k| broken.shuffle(k) != broken.shuffle(j)));
+    }
+
+    #[test]
+    fn shuffle_many() {
+        let randomizer = BlackRockGenerator::with_seed(100_003, 6);
+        let input = (0..100_003).collect::<Vec<_>>();
+
+        let scalar = input.iter().map(|&m| randomizer.shuffle(m)).collect::<Vec<_>>();
+
+        let mut out = vec![0; input.len()];
+        randomizer.shuffle_into(&input, &mut out);
+        assert_eq!(out, scalar);
+
+        // both round parities and a short tail
+        for rounds in [0, 3, 4] {
+            let randomizer = BlackRockGenerator::with_seed_and_rounds(1025, 6, rounds);
+            let mut values = (0..1025).rev().collect::<Vec<_>>();
+            randomizer.shuffle_many(&mut values);
+            assert!(values.into_iter().eq((0..1025).rev().map(|m| randomizer.shuffle(m))));
+        }
+
+        let mut in_place = input.clone();
+        randomizer.shuffle_many(&mut in_place);
+        assert_eq!(in_place, scalar);
+
+        in_place.sort_unstable();
+        assert_eq!(in_place, input);
+    }
+
+    #[test]
+    fn shuffle_order() {
+        for range in [0, 1, 2, 1000, 4099] {
+            let randomizer = BlackRockGenerator::with_seed(range, 6);
+            let mut buf = (0..range).collect::<Vec<_>>();
+            randomizer.shuffle_order(&mut buf);
+            assert!((0..range).all(|i| buf[randomizer.shuffle(i) as usize] == i));
+
+            buf.sort_unstable();
+            assert_eq!(buf, (0..range).collect::<Vec<_>>());
+        }
+
+        let randomizer = BlackRockGenerator::with_seed(4, 6);
+        let mut names = ["a", "b", "c", "d"];
+        randomizer.shuffle_order(&mut names);
+        assert!((0..4).all(|i| names[randomizer.shuffle(i) as usize] == ["a", "b", "c", "d"][i as usize]));
+    }
+
+    #[test]
+    #[should_panic = "buffer length must equal the range"]
+    fn shuffle_order_mismatched() {
+        BlackRockGenerator::with_seed(10, 6).shuffle_order(&mut [0u8; 9]);
+    }
+
+    #[test]
+    #[should_panic = "same length"]
+    fn shuffle_into_mismatched() {
+        BlackRockGenerator::with_seed(10, 6).shuffle_into(&[1, 2], &mut [0]);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn from_parts() {
+        let randomizer = BlackRockGenerator::new(10_000);
+        assert_eq!((randomizer.range(), randomizer.rounds()), (10_000, 3));
+
+        let rebuilt = BlackRockGenerator::with_seed_and_rounds(randomizer.range(), randomizer.seed(), randomizer.rounds());
+        assert_eq!(rebuilt.permutation(), randomizer.permutation());
+    }
+
+    #[test]
+    fn config_round_trip() {
+        #[allow(unused_mut)]
+        let mut generators = vec![
+            BlackRockGenerator::with_seed(10_000, 3),
+            BlackRockGenerator::with_seed_and_rounds(10_000, 3, 7),
+            BlackRockGenerator::with_auto_rounds(u64::MAX, 3),
+            BlackRockGenerator::for_target_count(0, 3, 2),
+            BlackRockGenerator::for_tenant(1 << 32, 3, 42, 3),
+            BlackRockGenerator::with_key(1 << 32, b"scan", 3),
+        ];
+        #[cfg(feature = "rand")]
+        generators.extend([BlackRockGenerator::new(10_000), BlackRockGenerator::with_rounds(10_000, 5)]);
+
+        for randomizer in generators {
+            let decoded = BlackRockGenerator::from_bytes(&randomizer.to_bytes()).unwrap();
+            assert_eq!(decoded.config(), randomizer.config());
+            assert_eq!(decoded.config(), (randomizer.range(), randomizer.seed(), randomizer.rounds()));
+
+            let (range, seed, rounds) = decoded.config();
+            let rebuilt = BlackRockGenerator::with_seed_and_rounds(range, seed, rounds);
+            assert!((0..range.min(1000)).all(|m| rebuilt.shuffle(m) == randomizer.shuffle(m)));
+
+            #[cfg(feature = "serde")]
+            {
+                let json = serde_json::to_string(&randomizer).unwrap();
+                let deserialized = serde_json::from_str::<BlackRockGenerator>(&json).unwrap();
+                assert_eq!(deserialized.config(), randomizer.config());
+                assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+                assert!((0..range.min(1000)).all(|m| deserialized.shuffle(m) == randomizer.shuffle(m)));
+            }
+        }
+    }
+
+    #[test]
+    fn inverted() {
+        let randomizer = BlackRockGenerator::with_seed(1000, 4);
+        let forward = randomizer.permutation();
+
+        let inverse = randomizer.inverted();
+        assert!((0..1000).all(|x| inverse.shuffle(forward[x as usize]) == x));
+        assert!((0..1000).all(|x| inverse.unshuffle(x) == forward[x as usize]));
+
+        assert_eq!(inverse.inverted().permutation(), forward);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize() {
+        use zeroize::Zeroize;
+
+        // `ZeroizeOnDrop` means a `Drop` impl, so the generator is `Clone` but no longer `Copy`
+        fn scrubbed_on_drop<T: Zeroize + zeroize::ZeroizeOnDrop + Clone>() {}
+        scrubbed_on_drop::<BlackRockGenerator>();
+
+        let mut randomizer = BlackRockGenerator::with_seed(1000, 0x5eed);
+        randomizer.zeroize();
+        assert_eq!(randomizer.seed(), 0);
+        assert_eq!(randomizer.seed_state, (0, 0));
+        assert_eq!((randomizer.range(), randomizer.rounds()), (1000, 3));
+    }
+
+    #[test]
+    fn composed() {
+        let (first, second) = (BlackRockGenerator::with_seed(1000, 4), BlackRockGenerator::with_seed(1000, 5));
+        let composed = BlackRockGenerator::with_seed(1000, 4).then(BlackRockGenerator::with_seed(1000, 5));
+
+        let mut seen = HashSet::new();
+        for m in 0..1000 {
+            let c = composed.shuffle(m);
+            assert_eq!(c, second.shuffle(first.shuffle(m)));
+            assert!(c < 1000 && seen.insert(c));
+            assert_eq!(composed.unshuffle(c), m);
+        }
+        assert_ne!(composed.shuffle(1), first.shuffle(1));
+
+        let (a, b) = composed.into_parts();
+        assert_eq!((a.seed(), b.seed()), (4, 5));
+    }
+
+    #[test]
+    #[should_panic = "same range"]
+    fn composed_mismatched_ranges() {
+        let _ = BlackRockGenerator::with_seed(1000, 4).then(BlackRockGenerator::with_seed(1001, 4));
+    }
+
+    #[test]
+    fn materialize_limit() {
+        let randomizer = BlackRockGenerator::with_seed(1000, 4);
+        assert_eq!(randomizer.materialized_bytes(), 8000);
+        assert_eq!(randomizer.try_permutation(8000), Ok(randomizer.permutation()));
+        assert_eq!(randomizer.try_permutation(7999), Err(MaterializeError { bytes: 8000, max_bytes: 7999 }));
+
+        let huge = BlackRockGenerator::with_seed(1 << 62, 4);
+        assert_eq!(huge.materialized_bytes(), u64::MAX);
+        assert!(huge.try_permutation(u64::MAX).is_err());
     }
 }
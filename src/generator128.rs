@@ -0,0 +1,237 @@
+//! The Blackrock cipher over `u128` ranges, for spaces like IPv6 subnets that don't fit in a `u64`.
+
+use crate::feistel::Split;
+use crate::generator::{sealed, sip_seed_state, RoundFunction, SipRound};
+
+/// Like [`BlackRockGenerator`](crate::generator::BlackRockGenerator), but shuffles `0..range` for a `u128` range.
+///
+/// Both Feistel halves are at most 64 bits wide, so the rounds run on the same 64 bit [`RoundFunction`]
+/// and Feistel network, only the split and the cycle walk work on `u128`.
+#[derive(Clone)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[must_use = "this generator does nothing unless driven"]
+pub struct BlackRockGenerator128<F = SipRound> {
+    range: u128,
+    seed: u64,
+    rounds: usize,
+    split: Split,
+    // the seed half of the sip state every round starts from, see `BlackRockGenerator`
+    seed_state: (u64, u64),
+    round_fn: F,
+}
+
+/// Only shows the config and the round function, like the `Debug` of
+/// [`BlackRockGenerator`](crate::generator::BlackRockGenerator).
+impl<F: core::fmt::Debug> core::fmt::Debug for BlackRockGenerator128<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BlackRockGenerator128")
+            .field("range", &self.range)
+            .field("seed", &self.seed)
+            .field("rounds", &self.rounds)
+            .field("round_fn", &self.round_fn)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Scrubs the seed and the split, the range and rounds are kept.
+/// As for [`BlackRockGenerator`](crate::generator::BlackRockGenerator), this rules out `Copy`.
+#[cfg(feature = "zeroize")]
+impl<F> zeroize::Zeroize for BlackRockGenerator128<F> {
+    fn zeroize(&mut self) {
+        self.seed.zeroize();
+        self.seed_state.0.zeroize();
+        self.seed_state.1.zeroize();
+        self.split.a_bits.zeroize();
+        self.split.a_mask.zeroize();
+        self.split.b_mask.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<F> Drop for BlackRockGenerator128<F> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<F> zeroize::ZeroizeOnDrop for BlackRockGenerator128<F> {}
+
+impl BlackRockGenerator128 {
+    /// Create a new blackrock cipher with a specific range, seed, and rounds.
+    /// See [`BlackRockGenerator::with_seed_and_rounds`](crate::generator::BlackRockGenerator::with_seed_and_rounds) for more details.
+    /// As there, `rounds = 0` is the identity permutation.
+    pub const fn with_seed_and_rounds(range: u128, seed: u64, rounds: usize) -> Self {
+        Self::with_round_function(range, seed, rounds, SipRound)
+    }
+
+    /// Create a new `BlackRockGenerator128` with the provided seed and default rounds.
+    pub fn with_seed(range: u128, seed: u64) -> Self {
+        Self::with_seed_and_rounds(range, seed, 3)
+    }
+
+    #[cfg(feature = "rand")]
+    /// Create a new `BlackRockGenerator128` with a random seed and the provided rounds.
+    pub fn with_rounds(range: u128, rounds: usize) -> Self {
+        Self::with_seed_and_rounds(range, rand::random(), rounds)
+    }
+
+    #[cfg(feature = "rand")]
+    /// Create a new `BlackRockGenerator128` with a random seed and default rounds.
+    pub fn new(range: u128) -> Self {
+        Self::with_seed_and_rounds(range, rand::random(), 3)
+    }
+}
+
+impl<F: RoundFunction> BlackRockGenerator128<F> {
+    /// Like [`with_seed_and_rounds`](BlackRockGenerator128::with_seed_and_rounds), but mixes every round with `round_fn`
+    /// instead of [`SipRound`].
+    pub const fn with_round_function(range: u128, seed: u64, rounds: usize, round_fn: F) -> Self {
+        Self {
+            range,
+            seed,
+            rounds,
+            split: Split::new(range),
+            seed_state: sip_seed_state(seed),
+            round_fn,
+        }
+    }
+
+    #[inline]
+    pub(crate) const fn range(&self) -> u128 {
+        self.range
+    }
+
+    /// The seed the rounds are keyed with.
+    #[inline]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    #[inline]
+    fn round(&self, j: usize, right: u64) -> u64 {
+        self.round_fn.mix_with_seed_state(j as u64, right, self.seed, sealed::SeedState(self.seed_state))
+    }
+
+    #[inline]
+    fn encrypt(&self, m: u128) -> u128 {
+        self.split.encrypt(m, self.rounds, |j, right| self.round(j, right))
+    }
+
+    #[inline]
+    fn decrypt(&self, c: u128) -> u128 {
+        self.split.decrypt(c, self.rounds, |j, left| self.round(j, left))
+    }
+
+    pub fn shuffle(&self, m: u128) -> u128 {
+        let mut c = self.encrypt(m);
+        while c >= self.range {
+            c = self.encrypt(c);
+        }
+        c
+    }
+
+    /// The inverse of [`shuffle`](Self::shuffle), `unshuffle(shuffle(m)) == m` for every `m < range`.
+    ///
+    /// `c` must be less than `range`.
+    pub fn unshuffle(&self, c: u128) -> u128 {
+        debug_assert!(c < self.range, "{c} is outside of the range {}", self.range);
+
+        let mut m = self.decrypt(c);
+        while m >= self.range {
+            m = self.decrypt(m);
+        }
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::BlackRockGenerator;
+
+    #[test]
+    fn permutation() {
+        for range in [0, 1, 2, 1000, 4099] {
+            for rounds in [0, 3, 4] {
+                let randomizer = BlackRockGenerator128::with_seed_and_rounds(range, 5, rounds);
+
+                let mut seen = vec![false; range as usize];
+                for m in 0..range {
+                    let c = randomizer.shuffle(m);
+                    assert!(!std::mem::replace(&mut seen[c as usize], true), "duplicate {c}");
+                    assert_eq!(randomizer.unshuffle(c), m);
+                }
+                assert!(seen.into_iter().all(|x| x));
+            }
+        }
+    }
+
+    // the Feistel core is shared, so within `u64` ranges the generators agree and the `u64` tests
+    // of tiny ranges, zero rounds and round parities carry over
+    #[test]
+    fn matches_u64() {
+        for range in (1..=8).chain([1000, 4099, 100_000]) {
+            for seed in [0, 9, u64::MAX] {
+                for rounds in 0..=6 {
+                    let narrow = BlackRockGenerator::with_seed_and_rounds(range, seed, rounds);
+                    let wide = BlackRockGenerator128::with_seed_and_rounds(u128::from(range), seed, rounds);
+                    assert!((0..range.min(5000)).all(|m| u128::from(narrow.shuffle(m)) == wide.shuffle(u128::from(m))));
+                    assert!((0..range.min(5000)).all(|c| u128::from(narrow.unshuffle(c)) == wide.unshuffle(u128::from(c))));
+                }
+            }
+        }
+
+        let narrow = BlackRockGenerator::with_seed(u64::MAX, 9);
+        let wide = BlackRockGenerator128::with_seed(u128::from(u64::MAX), 9);
+        assert!((0..64).map(|i| u64::MAX / 64 * i).all(|m| u128::from(narrow.shuffle(m)) == wide.shuffle(u128::from(m))));
+    }
+
+    #[test]
+    fn custom_round_function() {
+        #[derive(Debug, Clone, Copy)]
+        struct Trivial;
+        impl RoundFunction for Trivial {
+            fn mix(&self, j: u64, right: u64, seed: u64) -> u64 {
+                right ^ seed ^ j
+            }
+        }
+
+        let narrow = BlackRockGenerator::with_round_function(1000, 5, 3, Trivial);
+        let wide = BlackRockGenerator128::with_round_function(1000, 5, 3, Trivial);
+        assert!((0..1000).all(|m| u128::from(narrow.shuffle(m)) == wide.shuffle(u128::from(m))));
+    }
+
+    #[test]
+    fn huge_ranges() {
+        for range in [1 << 64, (1 << 64) + 1, 1 << 100, u128::MAX] {
+            let randomizer = BlackRockGenerator128::with_seed(range, 3);
+            for m in (0..64).map(|i| range / 64 * i).chain([range - 1]) {
+                let c = randomizer.shuffle(m);
+                assert!(c < range);
+                assert_eq!(randomizer.unshuffle(c), m);
+            }
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize() {
+        use zeroize::Zeroize;
+
+        fn scrubbed_on_drop<T: Zeroize + zeroize::ZeroizeOnDrop + Clone>() {}
+        scrubbed_on_drop::<BlackRockGenerator128>();
+
+        let mut randomizer = BlackRockGenerator128::with_seed(1 << 100, 0x5eed);
+        randomizer.zeroize();
+        assert_eq!(randomizer.seed(), 0);
+        assert_eq!(randomizer.seed_state, (0, 0));
+        assert_eq!(randomizer.range(), 1 << 100);
+    }
+
+    #[test]
+    fn debug_hides_split() {
+        let debug = format!("{:?}", BlackRockGenerator128::with_seed(1 << 100, 7));
+        assert_eq!(debug, format!("BlackRockGenerator128 {{ range: {}, seed: 7, rounds: 3, round_fn: SipRound, .. }}", 1u128 << 100));
+    }
+}
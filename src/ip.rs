@@ -0,0 +1,607 @@
+//! Address generators beyond the plain IPv4 space of [`BlackRockIpGenerator`](crate::BlackRockIpGenerator).
+
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use core::ops::RangeInclusive;
+use crate::{BlackRockIter, BlackRockIter128};
+use crate::adapters::Complement;
+use crate::generator::BlackRockGenerator;
+
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    first: IpAddr,
+    // index one past the last address of this block in the combined domain
+    end: u64,
+}
+
+/// Shuffles IPv4 and IPv6 blocks together into a single scan order, yielding [`IpAddr`]s.
+///
+/// All blocks are laid out back to back into one index space which is shuffled as a whole,
+/// so both families are interleaved at random. Blocks should not overlap,
+/// an address covered by two blocks is yielded twice.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct BlackRockDualStackGenerator {
+    iter: BlackRockIter,
+    blocks: Vec<Block>,
+}
+
+impl BlackRockDualStackGenerator {
+    /// Create a new `BlackRockDualStackGenerator` over the given `(network, prefix)` blocks,
+    /// with a specific seed and rounds. Host bits of every network are ignored.
+    ///
+    /// # Panics
+    /// if a prefix is too long for its family, or the blocks hold more than `u64::MAX` addresses in total.
+    pub fn with_seed_and_rounds(v4_cidrs: &[(Ipv4Addr, u8)], v6_cidrs: &[(Ipv6Addr, u8)], seed: u64, rounds: usize) -> Self {
+        let v4 = v4_cidrs.iter().map(|&(net, prefix)| {
+            assert!(prefix <= 32, "invalid IPv4 prefix length {prefix}");
+            let size = 1u128 << (32 - prefix);
+            (IpAddr::V4(Ipv4Addr::from_bits(net.to_bits() & !(size - 1) as u32)), Some(size))
+        });
+
+        let v6 = v6_cidrs.iter().map(|&(net, prefix)| {
+            assert!(prefix <= 128, "invalid IPv6 prefix length {prefix}");
+            // a /0 holds 2^128 addresses, which doesn't even fit in a u128
+            let size = 1u128.checked_shl(128 - u32::from(prefix));
+            let host_mask = size.map_or(u128::MAX, |size| size - 1);
+            (IpAddr::V6(Ipv6Addr::from_bits(net.to_bits() & !host_mask)), size)
+        });
+
+        let mut end = 0u64;
+        let blocks = v4.chain(v6)
+            .map(|(first, size)| {
+                end = size
+                    .and_then(|size| u64::try_from(size).ok())
+                    .and_then(|size| end.checked_add(size))
+                    .expect("dual stack blocks hold more than u64::MAX addresses");
+                Block { first, end }
+            })
+            .collect();
+
+        Self {
+            iter: BlackRockIter::with_seed_and_rounds(end, seed, rounds),
+            blocks,
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    /// Create a new `BlackRockDualStackGenerator` with a random seed and default rounds.
+    /// See [`with_seed_and_rounds`](Self::with_seed_and_rounds) for more details.
+    pub fn new(v4_cidrs: &[(Ipv4Addr, u8)], v6_cidrs: &[(Ipv6Addr, u8)]) -> Self {
+        Self::with_seed_and_rounds(v4_cidrs, v6_cidrs, rand::random(), 3)
+    }
+
+    fn addr(&self, index: u64) -> IpAddr {
+        let block = self.blocks.partition_point(|block| block.end <= index);
+        let start = block.checked_sub(1).map_or(0, |prev| self.blocks[prev].end);
+
+        let offset = index - start;
+        match self.blocks[block].first {
+            IpAddr::V4(first) => IpAddr::V4(Ipv4Addr::from_bits(first.to_bits() + offset as u32)),
+            IpAddr::V6(first) => IpAddr::V6(Ipv6Addr::from_bits(first.to_bits() + u128::from(offset))),
+        }
+    }
+}
+
+impl Iterator for BlackRockDualStackGenerator {
+    type Item = IpAddr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|index| self.addr(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n).map(|index| self.addr(index))
+    }
+}
+
+impl DoubleEndedIterator for BlackRockDualStackGenerator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|index| self.addr(index))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth_back(n).map(|index| self.addr(index))
+    }
+}
+
+impl FusedIterator for BlackRockDualStackGenerator {}
+
+/// Shuffles the cross product of an IPv4 range and a list of ports, yielding `(Ipv4Addr, u16)` targets.
+///
+/// The product is treated as a single range of `ip_count * port_count` indices and shuffled by one generator,
+/// so unlike composing two generators there is no correlation between the address and the port order.
+/// A port listed twice is scanned twice.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct BlackRockTargetIter {
+    iter: BlackRockIter,
+    first: u32,
+    ports: Vec<u16>,
+}
+
+impl BlackRockTargetIter {
+    /// Create a new `BlackRockTargetIter` over every address in `ips`, both ends included, times every port in `ports`,
+    /// with a specific seed and rounds.
+    pub fn with_seed_and_rounds(ips: RangeInclusive<Ipv4Addr>, ports: &[u16], seed: u64, rounds: usize) -> Self {
+        let (first, last) = (ips.start().to_bits(), ips.end().to_bits());
+        let ip_count = match first <= last {
+            true => u64::from(last - first) + 1,
+            false => 0,
+        };
+
+        // at most 2^32 addresses times 2^16 distinct ports, only repeated ports can overflow
+        let range = ip_count.checked_mul(ports.len() as u64)
+            .expect("targets hold more than u64::MAX (address, port) pairs");
+
+        Self {
+            iter: BlackRockIter::with_seed_and_rounds(range, seed, rounds),
+            first,
+            ports: ports.to_vec(),
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    /// Create a new `BlackRockTargetIter` with a random seed and default rounds.
+    /// See [`with_seed_and_rounds`](Self::with_seed_and_rounds) for more details.
+    pub fn new(ips: RangeInclusive<Ipv4Addr>, ports: &[u16]) -> Self {
+        Self::with_seed_and_rounds(ips, ports, rand::random(), 3)
+    }
+
+    fn target(&self, index: u64) -> (Ipv4Addr, u16) {
+        let port_count = self.ports.len() as u64;
+        let addr = Ipv4Addr::from_bits(self.first + (index / port_count) as u32);
+        (addr, self.ports[(index % port_count) as usize])
+    }
+}
+
+impl Iterator for BlackRockTargetIter {
+    type Item = (Ipv4Addr, u16);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|index| self.target(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n).map(|index| self.target(index))
+    }
+}
+
+impl DoubleEndedIterator for BlackRockTargetIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|index| self.target(index))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth_back(n).map(|index| self.target(index))
+    }
+}
+
+impl FusedIterator for BlackRockTargetIter {}
+
+/// Shuffles every address of an IPv6 subnet, yielding [`Ipv6Addr`]s.
+///
+/// The full 2<sup>128</sup> address space doesn't fit in a `u128` range, so the generator covers one `/prefix` subnet,
+/// e.g. a `/64` for 2<sup>64</sup> hosts.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct BlackRockIpv6Generator {
+    iter: BlackRockIter128,
+    base: u128,
+}
+
+impl BlackRockIpv6Generator {
+    /// Create a new `BlackRockIpv6Generator` over `net/prefix` with a specific seed and rounds.
+    /// Host bits of `net` are ignored.
+    ///
+    /// # Panics
+    /// if `prefix` is 0 or larger than 128.
+    pub fn with_seed_and_rounds(net: Ipv6Addr, prefix: u8, seed: u64, rounds: usize) -> Self {
+        assert!((1..=128).contains(&prefix), "invalid IPv6 prefix length {prefix}, expected 1 to 128");
+
+        let size = 1u128 << (128 - prefix);
+        Self {
+            iter: BlackRockIter128::with_seed_and_rounds(size, seed, rounds),
+            base: net.to_bits() & !(size - 1),
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    /// Create a new `BlackRockIpv6Generator` over `net/prefix` with a random seed and default rounds.
+    /// See [`with_seed_and_rounds`](Self::with_seed_and_rounds) for more details.
+    pub fn new(net: Ipv6Addr, prefix: u8) -> Self {
+        Self::with_seed_and_rounds(net, prefix, rand::random(), 3)
+    }
+
+    /// The position of `addr` in the scan order, `None` if it is outside of the subnet.
+    pub fn position_of(&self, addr: Ipv6Addr) -> Option<u128> {
+        addr.to_bits().checked_sub(self.base).and_then(|host| self.iter.position_of(host))
+    }
+}
+
+impl Iterator for BlackRockIpv6Generator {
+    type Item = Ipv6Addr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|host| Ipv6Addr::from_bits(self.base + host))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n).map(|host| Ipv6Addr::from_bits(self.base + host))
+    }
+}
+
+impl DoubleEndedIterator for BlackRockIpv6Generator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|host| Ipv6Addr::from_bits(self.base + host))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth_back(n).map(|host| Ipv6Addr::from_bits(self.base + host))
+    }
+}
+
+impl FusedIterator for BlackRockIpv6Generator {}
+
+/// Scans whole `/8` blocks in a chosen order, shuffling the hosts within each block,
+/// created by [`BlackRockIpGenerator::by_top_octet_order`](crate::BlackRockIpGenerator::by_top_octet_order).
+///
+/// Every block gets its own permutation derived from the seed and its first octet,
+/// the same way [`BlackRockGenerator::for_tenant`] derives tenants.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct TopOctetOrder {
+    order: Vec<u8>,
+    next_block: usize,
+    block: BlackRockIter,
+    seed: u64,
+    rounds: usize,
+}
+
+impl TopOctetOrder {
+    const BLOCK: u64 = 1 << 24;
+
+    /// Scan the `/8` blocks starting with the octets in `order`, in that order, with a specific seed and rounds.
+    /// An octet listed twice is scanned twice.
+    pub fn with_seed_and_rounds(order: &[u8], seed: u64, rounds: usize) -> Self {
+        Self {
+            order: order.to_vec(),
+            next_block: 0,
+            block: BlackRockIter::default(),
+            seed,
+            rounds,
+        }
+    }
+}
+
+impl Iterator for TopOctetOrder {
+    type Item = Ipv4Addr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nth(0)
+    }
+
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        loop {
+            // skip whole blocks without shuffling them
+            let left = (self.block.range.end - self.block.range.start) as usize;
+            if n < left {
+                let host = self.block.nth(n)?;
+                let octet = self.order[self.next_block - 1];
+                return Some(Ipv4Addr::from_bits(u32::from(octet) << 24 | host as u32));
+            }
+            n -= left;
+
+            let &octet = self.order.get(self.next_block)?;
+            self.block = BlackRockIter {
+                range: 0..Self::BLOCK,
+                generator: BlackRockGenerator::for_tenant(Self::BLOCK, self.seed, u64::from(octet), self.rounds),
+                offset: 0,
+            };
+            self.next_block += 1;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let blocks = (self.order.len() - self.next_block) as u64;
+        let remaining = self.block.range.end - self.block.range.start + blocks * Self::BLOCK;
+        match usize::try_from(remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl FusedIterator for TopOctetOrder {}
+
+/// Shuffles the IPv4 space minus a list of excluded blocks, e.g. reserved or opted out networks,
+/// created by [`BlackRockIpGenerator::with_exclusions`](crate::BlackRockIpGenerator::with_exclusions).
+///
+/// Only the included addresses are shuffled and then mapped around the excluded blocks
+/// like [`BlackRockIter::over_complement`], so nothing is filtered and the size hint is exact.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct ExcludingGenerator(Complement);
+
+impl ExcludingGenerator {
+    /// Scan every IPv4 address outside of the `(network, prefix)` blocks in `excluded`, with a specific seed and rounds.
+    /// Host bits of every network are ignored, blocks may overlap.
+    ///
+    /// # Panics
+    /// if a prefix is larger than 32.
+    pub fn with_seed_and_rounds(excluded: &[(Ipv4Addr, u8)], seed: u64, rounds: usize) -> Self {
+        let excluded = excluded.iter()
+            .map(|&(net, prefix)| {
+                assert!(prefix <= 32, "invalid IPv4 prefix length {prefix}");
+                let size = 1u64 << (32 - prefix);
+                let start = u64::from(net.to_bits()) & !(size - 1);
+                start..start + size
+            })
+            .collect::<Vec<_>>();
+
+        Self(BlackRockIter::over_complement(1 << 32, &excluded, seed, rounds))
+    }
+}
+
+impl Iterator for ExcludingGenerator {
+    type Item = Ipv4Addr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|x| Ipv4Addr::from_bits(x as u32))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth(n).map(|x| Ipv4Addr::from_bits(x as u32))
+    }
+}
+
+impl DoubleEndedIterator for ExcludingGenerator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|x| Ipv4Addr::from_bits(x as u32))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth_back(n).map(|x| Ipv4Addr::from_bits(x as u32))
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for ExcludingGenerator {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl FusedIterator for ExcludingGenerator {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn dual_stack() {
+        let v4 = [(Ipv4Addr::new(10, 0, 0, 7), 28), (Ipv4Addr::new(192, 168, 1, 0), 30)];
+        let v6 = [("2001:db8::ff".parse().unwrap(), 120)];
+
+        let addrs = BlackRockDualStackGenerator::with_seed_and_rounds(&v4, &v6, 1, 3).collect::<Vec<_>>();
+        assert_eq!(addrs.len(), 16 + 4 + 256);
+
+        let unique = addrs.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), addrs.len());
+
+        let expected = (0..16).map(|i| IpAddr::V4(Ipv4Addr::new(10, 0, 0, i)))
+            .chain((0..4).map(|i| IpAddr::V4(Ipv4Addr::new(192, 168, 1, i))))
+            .chain((0..256).map(|i| IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, i))))
+            .collect::<HashSet<_>>();
+        assert_eq!(unique, expected);
+
+        // families are interleaved, not one after the other
+        let first_v6 = addrs.iter().position(IpAddr::is_ipv6).unwrap();
+        assert!(addrs[first_v6..].iter().any(IpAddr::is_ipv4));
+
+        let mut iter = BlackRockDualStackGenerator::with_seed_and_rounds(&v4, &v6, 1, 3);
+        iter.nth(100);
+        assert!(iter.clone().eq(iter));
+    }
+
+    #[test]
+    #[should_panic = "more than u64::MAX"]
+    fn too_large() {
+        let _ = BlackRockDualStackGenerator::with_seed_and_rounds(&[], &[(Ipv6Addr::UNSPECIFIED, 64)], 1, 3);
+    }
+
+    #[test]
+    #[should_panic = "more than u64::MAX"]
+    fn whole_ipv6_space() {
+        let _ = BlackRockDualStackGenerator::with_seed_and_rounds(&[(Ipv4Addr::UNSPECIFIED, 30)], &[(Ipv6Addr::UNSPECIFIED, 0)], 1, 3);
+    }
+
+    #[test]
+    fn targets() {
+        let ips = Ipv4Addr::new(10, 0, 0, 250)..=Ipv4Addr::new(10, 0, 1, 4);
+        let ports = [22, 80, 443, 8080];
+
+        let mut iter = BlackRockTargetIter::with_seed_and_rounds(ips.clone(), &ports, 5, 3);
+        assert_eq!(iter.size_hint(), (11 * 4, Some(11 * 4)));
+        iter.nth(9);
+        assert_eq!(iter.size_hint(), (11 * 4 - 10, Some(11 * 4 - 10)));
+
+        let targets = BlackRockTargetIter::with_seed_and_rounds(ips, &ports, 5, 3).collect::<Vec<_>>();
+        let unique = targets.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), targets.len());
+
+        let expected = (250..=260u32)
+            .flat_map(|host| ports.map(|port| (Ipv4Addr::from_bits(0x0a00_0000 + host), port)))
+            .collect::<HashSet<_>>();
+        assert_eq!(unique, expected);
+
+        // the last address doesn't overflow
+        let last = Ipv4Addr::BROADCAST..=Ipv4Addr::BROADCAST;
+        assert_eq!(BlackRockTargetIter::with_seed_and_rounds(last, &[53], 5, 3).collect::<Vec<_>>(), [(Ipv4Addr::BROADCAST, 53)]);
+
+        let backwards = Ipv4Addr::new(10, 0, 0, 2)..=Ipv4Addr::new(10, 0, 0, 1);
+        assert_eq!(BlackRockTargetIter::with_seed_and_rounds(backwards, &ports, 5, 3).next(), None);
+        assert_eq!(BlackRockTargetIter::with_seed_and_rounds(Ipv4Addr::UNSPECIFIED..=Ipv4Addr::BROADCAST, &[], 5, 3).next(), None);
+    }
+
+    #[test]
+    fn exclusions() {
+        let excluded = [
+            (Ipv4Addr::new(0, 0, 0, 0), 8),
+            (Ipv4Addr::new(10, 1, 2, 3), 8),
+            (Ipv4Addr::new(10, 20, 0, 0), 16),
+            (Ipv4Addr::new(224, 0, 0, 0), 3),
+            (Ipv4Addr::new(255, 255, 255, 255), 32),
+        ];
+        let included = (1 << 32) - (2 + 32) * (1 << 24);
+
+        let mut iter = ExcludingGenerator::with_seed_and_rounds(&excluded, 4, 3);
+        assert_eq!(iter.size_hint(), (included, Some(included)));
+        assert_eq!(iter.len(), included);
+
+        let mut sampled = iter.by_ref().take(100_000).collect::<HashSet<_>>();
+        sampled.extend(iter.by_ref().rev().take(100_000));
+        assert_eq!(sampled.len(), 200_000);
+        assert!(sampled.iter().all(|addr| !matches!(addr.octets()[0], 0 | 10 | 224..)));
+        assert_eq!(iter.size_hint(), (included - 200_000, Some(included - 200_000)));
+        assert_eq!(iter.len(), included - 200_000);
+
+        // skipping from the back lands where stepping does, on a fork
+        let mut fork = iter.clone();
+        let stepped = (0..1000).map(|_| fork.next_back()).last().flatten();
+        assert_eq!(iter.nth_back(999), stepped);
+        assert!(iter.take(1000).eq(fork.take(1000)));
+
+        // exclude everything but 1.2.0.0/16, 100.64.0.0/17 and 100.65.0.0/16, small enough to check exactly
+        let excluded = [
+            (Ipv4Addr::new(0, 0, 0, 0), 8),
+            (Ipv4Addr::new(1, 0, 0, 0), 15),
+            (Ipv4Addr::new(1, 3, 0, 0), 16),
+            (Ipv4Addr::new(1, 4, 0, 0), 14),
+            (Ipv4Addr::new(1, 8, 0, 0), 13),
+            (Ipv4Addr::new(1, 16, 0, 0), 12),
+            (Ipv4Addr::new(1, 32, 0, 0), 11),
+            (Ipv4Addr::new(1, 64, 0, 0), 10),
+            (Ipv4Addr::new(1, 128, 0, 0), 9),
+            (Ipv4Addr::new(2, 0, 0, 0), 7),
+            (Ipv4Addr::new(4, 0, 0, 0), 6),
+            (Ipv4Addr::new(8, 0, 0, 0), 5),
+            (Ipv4Addr::new(16, 0, 0, 0), 4),
+            (Ipv4Addr::new(32, 0, 0, 0), 3),
+            (Ipv4Addr::new(64, 0, 0, 0), 3),
+            (Ipv4Addr::new(96, 0, 0, 0), 6),
+            (Ipv4Addr::new(100, 0, 0, 0), 10),
+            (Ipv4Addr::new(100, 64, 128, 0), 17),
+            (Ipv4Addr::new(100, 66, 0, 0), 15),
+            (Ipv4Addr::new(100, 68, 0, 0), 14),
+            (Ipv4Addr::new(100, 72, 0, 0), 13),
+            (Ipv4Addr::new(100, 80, 0, 0), 12),
+            (Ipv4Addr::new(100, 96, 0, 0), 11),
+            (Ipv4Addr::new(100, 128, 0, 0), 9),
+            (Ipv4Addr::new(101, 0, 0, 0), 8),
+            (Ipv4Addr::new(102, 0, 0, 0), 7),
+            (Ipv4Addr::new(104, 0, 0, 0), 5),
+            (Ipv4Addr::new(112, 0, 0, 0), 4),
+            (Ipv4Addr::new(128, 0, 0, 0), 1),
+        ];
+        let addrs = ExcludingGenerator::with_seed_and_rounds(&excluded, 4, 3).collect::<Vec<_>>();
+        let expected = (0..1 << 16).map(|host| Ipv4Addr::from_bits(0x0102_0000 | host))
+            .chain((0..1 << 15).map(|host| Ipv4Addr::from_bits(0x6440_0000 | host)))
+            .chain((0..1 << 16).map(|host| Ipv4Addr::from_bits(0x6441_0000 | host)))
+            .collect::<HashSet<_>>();
+        assert_eq!(addrs.len(), expected.len());
+        assert_eq!(addrs.into_iter().collect::<HashSet<_>>(), expected);
+
+        let all = ExcludingGenerator::with_seed_and_rounds(&[], 4, 3).take(1000);
+        assert!(all.eq(BlackRockIter::with_seed_and_rounds(1 << 32, 4, 3).take(1000).map(|x| Ipv4Addr::from_bits(x as u32))));
+    }
+
+    #[test]
+    fn ipv6_subnet() {
+        let net = "2001:db8::1234".parse().unwrap();
+        let addrs = BlackRockIpv6Generator::with_seed_and_rounds(net, 116, 4, 3).collect::<Vec<_>>();
+        assert_eq!(addrs.len(), 4096);
+
+        let unique = addrs.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), 4096);
+        assert!((0..4096).all(|host| unique.contains(&Ipv6Addr::from_bits(0x2001_0db8 << 96 | 0x1000 | host))));
+
+        let generator = BlackRockIpv6Generator::with_seed_and_rounds(net, 116, 4, 3);
+        assert!(addrs.iter().enumerate().all(|(i, &addr)| generator.position_of(addr) == Some(i as u128)));
+        assert_eq!(generator.position_of(Ipv6Addr::UNSPECIFIED), None);
+    }
+
+    #[test]
+    fn ipv6_huge_subnet() {
+        let net = "2001:db8::".parse::<Ipv6Addr>().unwrap();
+        let mut generator = BlackRockIpv6Generator::with_seed_and_rounds(net, 32, 4, 3);
+        assert_eq!(generator.size_hint().1, None);
+
+        let first = generator.by_ref().take(1000).collect::<HashSet<_>>();
+        assert_eq!(first.len(), 1000);
+        assert!(first.iter().all(|addr| addr.to_bits() >> 96 == 0x2001_0db8));
+
+        let fork = generator.clone();
+        assert!(generator.take(1000).eq(fork.take(1000)));
+    }
+
+    #[test]
+    fn top_octet_order() {
+        let order = [10, 3, 200];
+        let block = 1 << 24;
+
+        // one /8 is covered exactly, streamed into a bitset
+        let mut seen = vec![0u64; block / 64];
+        let mut first = Vec::new();
+        for (i, addr) in TopOctetOrder::with_seed_and_rounds(&order[..1], 6, 3).enumerate() {
+            assert_eq!(addr.octets()[0], 10);
+            let host = (addr.to_bits() & 0xff_ffff) as usize;
+            assert_eq!(seen[host / 64] & 1 << (host % 64), 0, "duplicate {addr}");
+            seen[host / 64] |= 1 << (host % 64);
+            if i < 100 {
+                first.push(host);
+            }
+        }
+        assert!(seen.iter().all(|&word| word == u64::MAX));
+        assert_ne!(first, (0..100).collect::<Vec<_>>());
+
+        // blocks come in the requested order, each shuffled on its own
+        let mut iter = TopOctetOrder::with_seed_and_rounds(&order, 6, 3);
+        assert_eq!(iter.size_hint(), (3 * block, Some(3 * block)));
+        assert!(iter.by_ref().take(1000).all(|addr| addr.octets()[0] == 10));
+        assert_eq!(iter.size_hint(), (3 * block - 1000, Some(3 * block - 1000)));
+
+        for &octet in &order[1..] {
+            let start = iter.nth(block - 1000 - 1).unwrap();
+            assert!(start.octets()[0] != octet);
+            let prefix = iter.by_ref().take(1000).collect::<Vec<_>>();
+            assert!(prefix.iter().all(|addr| addr.octets()[0] == octet));
+            assert_eq!(prefix.iter().collect::<HashSet<_>>().len(), 1000);
+        }
+        assert_eq!(iter.size_hint(), (block - 1000, Some(block - 1000)));
+
+        // a fork resumes mid block, in the same block permutation
+        let fork = iter.clone();
+        assert!(iter.take(1000).eq(fork.take(1000)));
+
+        assert_eq!(TopOctetOrder::with_seed_and_rounds(&[], 6, 3).next(), None);
+    }
+}
@@ -12,19 +12,63 @@
 //!     println!("{ip}")
 //! }
 //! ```
+//!
+//! # Features
+//! - `std` (default): everything that needs the standard library, like hash sets and I/O.
+//!   Without it the crate is `no_std`, but still needs `alloc`.
+//!   The core cipher ([`BlackRockGenerator::with_seed_and_rounds`], [`shuffle`](BlackRockGenerator::shuffle),
+//!   [`BlackRockIter`], ...) is plain integer math and always available.
+//! - `rand` (default): the constructors that pick a random seed, like [`BlackRockGenerator::new`].
+//! - `bloom`: [`bloom::BloomFilter`] and [`BlackRockIter::skip_bloom`].
+//! - `rayon`: [`BlackRockGenerator::par_shuffle`], a rayon parallel iterator over the permutation,
+//!   and [`BlackRockGenerator::permutation_par`].
+//! - `serde`: `Serialize` and `Deserialize` for [`BlackRockGenerator`], [`BlackRockIter`] and [`cursor::Cursor`].
+//! - `zeroize`: scrub the seed of a [`BlackRockGenerator`] or [`BlackRockGenerator128`] on drop, which makes it and its wrappers `Clone` but not `Copy`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// `is_multiple_of` is only stable since 1.87, `%` keeps older toolchains building
+#![allow(clippy::manual_is_multiple_of)]
 
+extern crate alloc;
 
-use std::iter::FusedIterator;
-use std::net::Ipv4Addr;
-use std::ops::Range;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+use core::iter::FusedIterator;
+use core::num::NonZero;
+use core::net::Ipv4Addr;
+use core::ops::{Bound, Range, RangeBounds, RangeInclusive};
 use crate::generator::BlackRockGenerator;
+use crate::generator128::BlackRockGenerator128;
 
 pub mod generator;
+pub mod generator128;
+mod feistel;
+pub mod adapters;
+pub mod error;
+pub mod stable;
+pub mod ip;
+pub mod cursor;
+pub mod window;
+pub mod builder;
+#[cfg(feature = "bloom")]
+pub mod bloom;
 
+const PAYLOAD_TAG: u64 = u64::MAX;
+// u64::MAX - 1 is taken by `BlackRockGenerator::for_tenant`
+const TTL_TAG: u64 = u64::MAX - 2;
+const SRC_PORT_TAG: u64 = u64::MAX - 3;
+const RETRY_TAG: u64 = u64::MAX - 4;
 
+#[derive(Clone)]
 pub struct BlackRockIter {
     range: Range<u64>,
-    generator: BlackRockGenerator
+    generator: BlackRockGenerator,
+    // added to every shuffled value, for ranges that don't start at zero
+    offset: u64,
 }
 
 impl Default for BlackRockIter {
@@ -41,6 +85,7 @@ impl BlackRockIter {
         Self {
             range: 0..range,
             generator: BlackRockGenerator::with_seed_and_rounds(range, seed, rounds),
+            offset: 0,
         }
     }
 
@@ -49,31 +94,545 @@ impl BlackRockIter {
         Self {
             range: 0..range,
             generator: BlackRockGenerator::with_seed(range, seed),
+            offset: 0,
         }
     }
 
+    /// Create a new `BlackRockIter` with the fixed [`DETERMINISTIC_SEED`](generator::DETERMINISTIC_SEED) and default rounds,
+    /// see [`BlackRockGenerator::deterministic`].
+    pub const fn deterministic(range: u64) -> Self {
+        Self::with_seed_and_rounds(range, generator::DETERMINISTIC_SEED, 3)
+    }
+
+    #[cfg(feature = "rand")]
     /// Create a new `BlackRockIter` with a random seed and the provided rounds.
     pub fn with_rounds(range: u64, rounds: usize) -> Self {
         Self {
             range: 0..range,
             generator: BlackRockGenerator::with_rounds(range, rounds),
+            offset: 0,
         }
     }
 
+    #[cfg(feature = "rand")]
     /// Create a new `BlackRockIter` with a random seed and default rounds.
     pub fn new(range: u64) -> Self {
         Self {
             range: 0..range,
             generator: BlackRockGenerator::new(range),
+            offset: 0,
+        }
+    }
+
+    /// Create a new `BlackRockIter` over the window `range`, e.g. `1024..65536` to cover
+    /// the registered and dynamic ports without walking `0..1024`.
+    ///
+    /// The length of the window is shuffled and `range.start` added to every value,
+    /// so the outputs are a permutation of `range`.
+    pub fn with_range(range: Range<u64>, seed: u64, rounds: usize) -> Self {
+        let len = range.end.saturating_sub(range.start);
+        Self {
+            range: 0..len,
+            generator: BlackRockGenerator::with_seed_and_rounds(len, seed, rounds),
+            offset: range.start,
+        }
+    }
+
+    /// Create a new `BlackRockIter` over every value of `range`, both endpoints included,
+    /// e.g. `100..=200` yields 101 values.
+    ///
+    /// # Panics
+    /// if the range is `0..=u64::MAX`, 2<sup>64</sup> values don't fit in a `u64` length.
+    pub fn inclusive(range: RangeInclusive<u64>, seed: u64, rounds: usize) -> Self {
+        let len = match range.is_empty() {
+            true => 0,
+            false => (range.end() - range.start())
+                .checked_add(1)
+                .expect("an inclusive range over all of u64 is too long to shuffle"),
+        };
+
+        Self {
+            range: 0..len,
+            generator: BlackRockGenerator::with_seed_and_rounds(len, seed, rounds),
+            offset: *range.start(),
+        }
+    }
+
+    /// Create a new `BlackRockIter` over any range of `u64`s, e.g. `0..=65535`, `100..200` or `..1024`,
+    /// like [`with_range`](Self::with_range) and [`inclusive`](Self::inclusive) the outputs are a permutation of `range`.
+    ///
+    /// # Errors
+    /// if the range has no upper bound, or holds 2<sup>64</sup> values.
+    pub fn from_range_bounds(range: impl RangeBounds<u64>, seed: u64, rounds: usize) -> Result<Self, error::RangeBoundsError> {
+        // in u128 so that `..=u64::MAX` and an excluded `u64::MAX` start don't overflow
+        let start = match range.start_bound() {
+            Bound::Included(&start) => u128::from(start),
+            Bound::Excluded(&start) => u128::from(start) + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => u128::from(end) + 1,
+            Bound::Excluded(&end) => u128::from(end),
+            Bound::Unbounded => return Err(error::RangeBoundsError::Unbounded),
+        };
+
+        let len = u64::try_from(end.saturating_sub(start)).map_err(|_| error::RangeBoundsError::TooLong)?;
+        Ok(Self {
+            range: 0..len,
+            generator: BlackRockGenerator::with_seed_and_rounds(len, seed, rounds),
+            // only an empty range can start at 2^64
+            offset: start as u64,
+        })
+    }
+
+    /// Create an iterator over everything in `0..full_range` except the `excluded` ranges,
+    /// each remaining value exactly once.
+    ///
+    /// Only the remaining values are shuffled and then mapped around the holes, so nothing is
+    /// ever retried or filtered. Excluded ranges may overlap, be unsorted, or reach past `full_range`.
+    pub fn over_complement(full_range: u64, excluded: &[Range<u64>], seed: u64, rounds: usize) -> adapters::Complement {
+        let mut excluded = excluded.iter()
+            .map(|hole| hole.start.min(full_range)..hole.end.min(full_range))
+            .filter(|hole| !hole.is_empty())
+            .collect::<Vec<_>>();
+        excluded.sort_unstable_by_key(|hole| hole.start);
+
+        let mut merged: Vec<Range<u64>> = Vec::with_capacity(excluded.len());
+        for hole in excluded {
+            match merged.last_mut() {
+                Some(last) if hole.start <= last.end => last.end = last.end.max(hole.end),
+                _ => merged.push(hole),
+            }
+        }
+
+        let mut skipped = 0;
+        let holes = merged.into_iter()
+            .map(|hole| {
+                let kept_before = hole.start - skipped;
+                skipped += hole.end - hole.start;
+                (kept_before, skipped)
+            })
+            .collect();
+
+        adapters::Complement {
+            iter: Self::with_seed_and_rounds(full_range - skipped, seed, rounds),
+            holes,
+        }
+    }
+
+    #[inline]
+    fn value(&self, pos: u64) -> u64 {
+        self.generator.shuffle(pos) + self.offset
+    }
+
+    /// Create an iterator over `0..range` that switches to a fresh seed every `every` values.
+    ///
+    /// The `n`th block of `every` values is shuffled with the seed `base_seed + n` (wrapping),
+    /// so a rotating scan is reproducible from its parameters alone.
+    /// Each block is drawn from a different permutation, so unlike a plain `BlackRockIter`
+    /// a full pass **will** repeat some values and miss others.
+    ///
+    /// # Panics
+    /// if `every` is zero.
+    pub fn with_rotation(range: u64, base_seed: u64, rounds: usize, every: u64) -> adapters::Rotating {
+        assert_ne!(every, 0, "rotation interval must be non-zero");
+
+        adapters::Rotating {
+            range: 0..range,
+            generator: BlackRockGenerator::with_seed_and_rounds(range, base_seed, rounds),
+            base_seed,
+            rounds,
+            every,
+            emitted: 0,
+        }
+    }
+
+    /// The position of `value` in the full shuffled sequence, i.e. how many values come before it,
+    /// regardless of how much of the iterator has been consumed.
+    ///
+    /// Returns `None` if `value` is outside of the range.
+    pub fn position_of(&self, value: u64) -> Option<u64> {
+        value.checked_sub(self.offset)
+            .filter(|&value| value < self.generator.range())
+            .map(|value| self.generator.unshuffle(value))
+    }
+
+    /// Returns `true` if nothing has been consumed from either end yet,
+    /// i.e. the iterator still covers the whole permutation.
+    pub fn is_fresh(&self) -> bool {
+        self.range == (0..self.generator.range())
+    }
+
+    /// Returns `true` if `other` walks the exact same permutation, i.e. both share the same
+    /// range, offset, seed and rounds, regardless of how far either has been consumed.
+    ///
+    /// Shards are only disjoint if they all slice one permutation,
+    /// so coordinators can check this before handing them out.
+    pub fn same_permutation_as(&self, other: &Self) -> bool {
+        let config = |iter: &Self| (iter.generator.range(), iter.offset, iter.generator.seed(), iter.generator.rounds());
+        config(self) == config(other)
+    }
+
+    #[inline]
+    fn map_positions<T>(
+        self,
+        mut f: impl FnMut(&BlackRockGenerator, u64, u64) -> T
+    ) -> impl DoubleEndedIterator<Item = T> + FusedIterator {
+        let Self { range, generator, offset } = self;
+        range.map(move |pos| f(&generator, pos, generator.shuffle(pos) + offset))
+    }
+
+    /// Pair every value with a deterministic `u64` derived from its position in the sequence,
+    /// e.g. to seed a per-target probe payload without a separate RNG.
+    ///
+    /// The payload seed is a keyed hash of the position under the generator's seed,
+    /// so the same config always reproduces the same pairs.
+    pub fn with_payload_seed(self) -> impl DoubleEndedIterator<Item = (u64, u64)> + FusedIterator {
+        self.map_positions(|generator, pos, x| (x, generator.hash(PAYLOAD_TAG, pos)))
+    }
+
+    /// Pair every value with a [`Cursor`](cursor::Cursor) that resumes the sequence right after it,
+    /// so a checkpoint can be persisted at any point.
+    ///
+    /// The cursor tracks front to back iteration, so unlike the iterator itself this can't be reversed.
+    pub fn with_cursor(self) -> impl FusedIterator<Item = (u64, cursor::Cursor)> {
+        let (end, offset) = (self.range.end, self.offset);
+        self.map_positions(move |generator, pos, x| {
+            let cursor = cursor::Cursor {
+                range: generator.range(),
+                offset,
+                seed: generator.seed(),
+                rounds: generator.rounds(),
+                next: pos + 1,
+                end,
+            };
+            (x, cursor)
+        })
+    }
+
+    /// Snapshot the remaining sequence as a [`Cursor`](cursor::Cursor), e.g. to persist scan progress to disk.
+    ///
+    /// Unlike [`with_cursor`](Self::with_cursor) this also keeps track of values taken from the back.
+    /// Only the generator config is stored, the rest is recomputed when the cursor is [resumed](cursor::Cursor::resume).
+    pub fn checkpoint(&self) -> cursor::Cursor {
+        cursor::Cursor {
+            range: self.generator.range(),
+            offset: self.offset,
+            seed: self.generator.seed(),
+            rounds: self.generator.rounds(),
+            next: self.range.start,
+            end: self.range.end,
+        }
+    }
+
+    /// The position in the shuffled sequence of the value the next call to [`next`](Iterator::next) yields,
+    /// i.e. how many values have been taken from the front.
+    pub const fn position(&self) -> u64 {
+        self.range.start
+    }
+
+    /// Skip the next `n` values from the front without shuffling any of them,
+    /// so `advance_by(k)` followed by `next()` yields what `nth(k)` would.
+    ///
+    /// A stable stand-in for the unstable [`Iterator::advance_by`], taking a `u64` so a shard of a huge
+    /// range can be skipped in one step. [`nth`](Iterator::nth), and through it `skip` and `step_by`,
+    /// already only shuffle the values they land on.
+    ///
+    /// # Errors
+    /// if fewer than `n` values were left, with the number of steps that couldn't be taken.
+    /// The iterator is exhausted from the front then.
+    pub fn advance_by(&mut self, n: u64) -> Result<(), NonZero<u64>> {
+        let step = n.min(self.range.end - self.range.start);
+        self.range.start += step;
+        NonZero::new(n - step).map_or(Ok(()), Err)
+    }
+
+    /// [`advance_by`](Self::advance_by) from the back, a stable stand-in for
+    /// the unstable [`DoubleEndedIterator::advance_back_by`].
+    ///
+    /// # Errors
+    /// if fewer than `n` values were left, with the number of steps that couldn't be taken.
+    pub fn advance_back_by(&mut self, n: u64) -> Result<(), NonZero<u64>> {
+        let step = n.min(self.range.end - self.range.start);
+        self.range.end -= step;
+        NonZero::new(n - step).map_or(Ok(()), Err)
+    }
+
+    /// The next `n` values without advancing the iterator, e.g. to sanity check the order in logs.
+    pub fn preview(&self, n: usize) -> Vec<u64> {
+        self.clone().take(n).collect()
+    }
+
+    /// Rebuild an iterator over `0..range` that continues at `position`, e.g. one saved with
+    /// [`position`](Self::position) before a scan was stopped.
+    ///
+    /// Unlike [`nth`](Iterator::nth) nothing has to be constructed up front and fast-forwarded,
+    /// only the scalars have to be kept. A `position` past the end yields an empty iterator.
+    pub const fn resume_from(range: u64, seed: u64, rounds: usize, position: u64) -> Self {
+        let mut iter = Self::with_seed_and_rounds(range, seed, rounds);
+        iter.range.start = if position < range { position } else { range };
+        iter
+    }
+
+    /// An iterator over a reproducible uniform sample of `fraction` of `0..range`,
+    /// the first `round(range * fraction)` values of the shuffled sequence, each at most once.
+    ///
+    /// # Panics
+    /// if `fraction` is not in `[0, 1]`.
+    pub fn sample_fraction(range: u64, fraction: f64, seed: u64, rounds: usize) -> Self {
+        assert!((0.0..=1.0).contains(&fraction), "sample fraction must be in [0, 1], got {fraction}");
+
+        // `f64::round` needs std, the product is never negative so adding a half and truncating does the same.
+        let len = (range as f64 * fraction + 0.5) as u64;
+        let mut iter = Self::with_seed_and_rounds(range, seed, rounds);
+        iter.range.end = len.min(range);
+        iter
+    }
+
+    /// Pair every value with a source port in `port_range` derived from the value itself,
+    /// so a stateless scanner can check a response against the port its target must have been probed from.
+    ///
+    /// Unlike [`with_ttl`](Self::with_ttl) this hashes the value, not the position,
+    /// under the generator's seed: the same target always gets the same port.
+    ///
+    /// # Panics
+    /// if `port_range` is empty.
+    pub fn with_src_port(self, port_range: RangeInclusive<u16>) -> impl DoubleEndedIterator<Item = (u64, u16)> + FusedIterator {
+        let (low, high) = port_range.into_inner();
+        assert!(low <= high, "port range {low}..={high} is empty");
+
+        let span = u64::from(high - low) + 1;
+        self.map_positions(move |generator, _, x| (x, low + (generator.hash(SRC_PORT_TAG, x) % span) as u16))
+    }
+
+    /// Pair every value with a retry budget in `budgets`, e.g. to give targets different retry counts pseudo-randomly.
+    ///
+    /// Like [`with_src_port`](Self::with_src_port) the budget is a keyed hash of the value,
+    /// so the same target always gets the same budget.
+    ///
+    /// # Panics
+    /// if `budgets` is empty.
+    pub fn with_retry_budget(self, budgets: RangeInclusive<u8>) -> impl DoubleEndedIterator<Item = (u64, u8)> + FusedIterator {
+        let (low, high) = budgets.into_inner();
+        assert!(low <= high, "retry budget range {low}..={high} is empty");
+
+        let span = u64::from(high - low) + 1;
+        self.map_positions(move |generator, _, x| (x, low + (generator.hash(RETRY_TAG, x) % span) as u8))
+    }
+
+    /// Tag every value with a sequence number counting up from `start`, `(start, first), (start + 1, second), ...`
+    /// e.g. to match responses to probes across resumes, where positions restart but the base can be carried over.
+    ///
+    /// Sequence numbers are tied to the position, so reversing yields the same pairs back to front.
+    /// They wrap around after `u64::MAX`.
+    pub fn with_sequence(self, start: u64) -> impl DoubleEndedIterator<Item = (u64, u64)> + FusedIterator {
+        let first = self.range.start;
+        self.map_positions(move |_, pos, x| (start.wrapping_add(pos - first), x))
+    }
+
+    /// Pair every value with a TTL in `ttl_range` derived from its position in the sequence,
+    /// e.g. to randomize probe TTLs while still being able to rebuild every probe.
+    ///
+    /// Like [`with_payload_seed`](Self::with_payload_seed) the TTL is a keyed hash of the position,
+    /// so the same config always reproduces the same pairs.
+    ///
+    /// # Panics
+    /// if `ttl_range` is empty.
+    pub fn with_ttl(self, ttl_range: RangeInclusive<u8>) -> impl DoubleEndedIterator<Item = (u64, u8)> + FusedIterator {
+        let (low, high) = ttl_range.into_inner();
+        assert!(low <= high, "TTL range {low}..={high} is empty");
+
+        let span = u64::from(high - low) + 1;
+        self.map_positions(move |generator, pos, x| (x, low + (generator.hash(TTL_TAG, pos) % span) as u8))
+    }
+
+    /// Pair every value with a hash chained over every value emitted so far, `h = sip(h, value)` starting from the seed,
+    /// so the last hash certifies the whole recorded sequence.
+    ///
+    /// The chain follows emission order, so unlike the iterator itself this can't be reversed.
+    pub fn with_chained_hash(self) -> impl FusedIterator<Item = (u64, u64)> {
+        let seed = self.generator.seed();
+        let mut h = seed;
+        self.map(move |x| {
+            h = generator::sip(h, x, seed);
+            (x, h)
+        })
+    }
+
+    /// Only shuffle the positions matching `predicate`, i.e. yield `shuffle(pos)` for every
+    /// remaining position `pos` in the sequence with `predicate(pos)`.
+    ///
+    /// The predicate sees positions, not values, so positions that are skipped are never shuffled.
+    pub fn at_positions<P: FnMut(u64) -> bool>(self, mut predicate: P) -> impl DoubleEndedIterator<Item = u64> + FusedIterator {
+        let Self { range, generator, offset } = self;
+        range
+            .filter(move |&pos| predicate(pos))
+            .map(move |pos| generator.shuffle(pos) + offset)
+    }
+
+    #[cfg(feature = "std")]
+    /// Continue the sequence while skipping every value in `probed`, a log of values that were
+    /// already scanned, e.g. recovered after an unclean shutdown.
+    ///
+    /// Values are looked up in the log only up to the furthest logged position (found through [`position_of`](Self::position_of)),
+    /// everything after it is emitted without lookups. Values the log is missing before that position,
+    /// like probes that were in flight, are still emitted so that nothing is lost.
+    pub fn skip_logged(self, probed: &HashSet<u64>) -> impl DoubleEndedIterator<Item = u64> + FusedIterator + '_ {
+        let furthest = probed.iter()
+            .filter_map(|&x| self.position_of(x))
+            .filter(|pos| self.range.contains(pos))
+            .max();
+
+        self.map_positions(|_, pos, x| (pos, x))
+            .filter(move |&(pos, x)| furthest.is_none_or(|furthest| pos > furthest) || !probed.contains(&x))
+            .map(|(_, x)| x)
+    }
+
+    /// Format every value as a zero-padded decimal string at least `width` characters wide,
+    /// e.g. `0000042` for a width of 7.
+    ///
+    /// Values with more than `width` digits are written out in full, never truncated.
+    pub fn as_padded_strings(self, width: usize) -> impl DoubleEndedIterator<Item = String> + FusedIterator {
+        self.map(move |x| format!("{x:0width$}"))
+    }
+
+    /// Yield the values in batches of 8, one 64 byte cache line each, so the next batch can be prefetched,
+    /// together with how many of them are values.
+    ///
+    /// Only the final batch can be short, it is padded to full length with `u64::MAX`. Use the length to tell
+    /// the padding apart: an offset iterator, e.g. from [`inclusive`](Self::inclusive), can yield `u64::MAX` itself.
+    pub fn cache_line_batches(mut self) -> impl FusedIterator<Item = ([u64; 8], usize)> {
+        core::iter::from_fn(move || {
+            let mut batch = [u64::MAX; 8];
+            batch[0] = self.next()?;
+            let mut len = 1;
+            for (slot, x) in batch[1..].iter_mut().zip(self.by_ref()) {
+                *slot = x;
+                len += 1;
+            }
+            Some((batch, len))
+        }).fuse()
+    }
+
+    /// Group the sequence into blocks of doubling size, 1 value, then 2, 4, 8 and so on,
+    /// e.g. to animate coverage growing as expanding randomized regions.
+    ///
+    /// Every block is the next slice of the shuffled sequence, the final block holds whatever is left
+    /// and may be shorter.
+    pub fn doubling_blocks(mut self) -> impl FusedIterator<Item = Vec<u64>> {
+        let mut size = 1usize;
+        core::iter::from_fn(move || {
+            let block = self.by_ref().take(size).collect::<Vec<_>>();
+            size = size.saturating_mul(2);
+            (!block.is_empty()).then_some(block)
+        }).fuse()
+    }
+
+    /// Drain the iterator, calling `route(value, shard)` for every value where `shard = (value - start) * shards / range`,
+    /// i.e. the shard owning the slice of the output space the value falls in, e.g. to route results to sharded storage.
+    ///
+    /// # Panics
+    /// if `shards` is zero.
+    pub fn route_by_value(self, shards: u64, mut route: impl FnMut(u64, u64)) {
+        assert_ne!(shards, 0, "need at least one shard");
+
+        let (range, offset) = (u128::from(self.generator.range()), self.offset);
+        self.for_each(|x| route(x, (u128::from(x - offset) * u128::from(shards) / range) as u64));
+    }
+
+    /// Emit every value matching `is_priority` before any other value.
+    ///
+    /// Both tiers keep the shuffled order of the underlying permutation,
+    /// this walks the remaining range twice, once per tier.
+    pub fn with_priority<F: Fn(u64) -> bool>(self, is_priority: F) -> adapters::Prioritized<F> {
+        adapters::Prioritized {
+            rest: self.range.clone(),
+            iter: self,
+            is_priority,
         }
     }
+
+    /// Assign every value to one of `n` queues in turn, yielding `(queue, value)` where `queue` cycles `0..n`,
+    /// so each queue gets a shuffled share that differs by at most one value from any other.
+    ///
+    /// The queue is the position in the sequence modulo `n`, so a resumed iterator keeps assigning the same queues.
+    ///
+    /// # Panics
+    /// if `n` is zero.
+    pub fn balanced_queues(self, n: usize) -> impl DoubleEndedIterator<Item = (usize, u64)> + FusedIterator {
+        assert_ne!(n, 0, "need at least one queue");
+        self.map_positions(move |_, pos, x| ((pos % n as u64) as usize, x))
+    }
+
+    /// Split the remaining values into `groups` groups by position modulo `groups`, yielding each group in turn.
+    ///
+    /// Every group takes every `groups`th value of the permutation, so each one is a uniform random sample
+    /// spread across the whole range, unlike contiguous chunks: scanning group after group covers everything
+    /// coarsely early on and gets denser with every group. Groups are lazy, and like
+    /// [`balanced_queues`](Self::balanced_queues) a resumed iterator keeps assigning the same groups.
+    ///
+    /// # Panics
+    /// if `groups` is zero.
+    pub fn stratified_groups(self, groups: usize) -> impl FusedIterator<Item = impl Iterator<Item = u64>> {
+        assert_ne!(groups, 0, "need at least one group");
+
+        let Self { range, generator, offset } = self;
+        let n = groups as u64;
+        (0..n).map(move |group| {
+            // the first remaining position in this group
+            let first = range.start.saturating_add((group + n - range.start % n) % n).min(range.end);
+            // a clone rather than a copy, the generator isn't `Copy` with the `zeroize` feature
+            let generator = Clone::clone(&generator);
+            (first..range.end).step_by(groups).map(move |pos| generator.shuffle(pos) + offset)
+        })
+    }
+
+    /// Tag every value with its time slot, its position in the sequence modulo `slots`,
+    /// for schedules where `slots` windows repeat cyclically.
+    ///
+    /// # Panics
+    /// if `slots` is zero.
+    pub fn with_slot(self, slots: u64) -> impl DoubleEndedIterator<Item = (u64, u64)> + FusedIterator {
+        assert_ne!(slots, 0, "need at least one slot");
+        self.map_positions(move |_, pos, x| (x, pos % slots))
+    }
+
+    /// Round-robin across `n` lanes, `lane_of(x)` picks the lane of every value.
+    ///
+    /// Each lane keeps the shuffled order of the underlying permutation, once a lane runs dry it is
+    /// skipped. Values pulled ahead for other lanes are buffered, so memory use grows with
+    /// how unevenly the values are spread across lanes.
+    ///
+    /// # Panics
+    /// if `n` is zero, and while iterating if `lane_of` returns a lane `>= n`.
+    pub fn lanes<F: Fn(u64) -> usize>(self, n: usize, lane_of: F) -> adapters::Lanes<F> {
+        assert_ne!(n, 0, "need at least one lane");
+
+        adapters::Lanes {
+            iter: self,
+            lane_of,
+            buffers: vec![alloc::collections::VecDeque::new(); n],
+            next_lane: 0,
+        }
+    }
+
+    /// Skip every value that `filter` reports as present.
+    ///
+    /// A Bloom filter can hold a huge recent-set in a fraction of the memory an exact set would need,
+    /// the price is that it has false positives: a value that was never inserted may still be skipped,
+    /// at roughly the filter's false positive rate. Values that *were* inserted are always skipped.
+    /// Use an exact set instead if every target must be visited.
+    #[cfg(feature = "bloom")]
+    pub fn skip_bloom(self, filter: &bloom::BloomFilter) -> impl DoubleEndedIterator<Item = u64> + FusedIterator + '_ {
+        self.filter(move |&x| !filter.contains(x))
+    }
 }
 
 impl Iterator for BlackRockIter {
     type Item = u64;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.range.next().map(|x| self.generator.shuffle(x))
+        self.range.next().map(|x| self.value(x))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -88,11 +647,130 @@ impl Iterator for BlackRockIter {
     }
     
     fn nth(&mut self, n: usize) -> Option<Self::Item> {
-        self.range.nth(n).map(|x| self.generator.shuffle(x))
+        self.range.nth(n).map(|x| self.value(x))
+    }
+
+    // loop over the inner range directly instead of going through `next`,
+    // `try_fold` can't be overridden on stable as `Try` is unstable
+    fn fold<B, G>(self, init: B, mut f: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        let Self { range, generator, offset } = self;
+        range.fold(init, |acc, x| f(acc, generator.shuffle(x) + offset))
+    }
+
+    fn for_each<G>(self, mut f: G)
+    where
+        G: FnMut(Self::Item),
+    {
+        self.fold((), |(), x| f(x));
     }
 }
 
 impl DoubleEndedIterator for BlackRockIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|x| self.value(x))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.range.nth_back(n).map(|x| self.value(x))
+    }
+
+    fn rfold<B, G>(self, init: B, mut f: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        let Self { range, generator, offset } = self;
+        range.rfold(init, |acc, x| f(acc, generator.shuffle(x) + offset))
+    }
+}
+
+impl FusedIterator for BlackRockIter {}
+
+// what the serde impls of `BlackRockIter` read and write, the generator is borrowed when serializing
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "BlackRockIter")]
+struct IterState<G> {
+    generator: G,
+    range: Range<u64>,
+    offset: u64,
+}
+
+/// Serialized as its generator, the remaining positions and the offset, so a half consumed iterator
+/// continues where it left off once deserialized.
+#[cfg(feature = "serde")]
+impl serde::Serialize for BlackRockIter {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let state = IterState { generator: &self.generator, range: self.range.clone(), offset: self.offset };
+        serde::Serialize::serialize(&state, serializer)
+    }
+}
+
+/// Rejects the same positions as [`Cursor::from_bytes`](cursor::Cursor::from_bytes).
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BlackRockIter {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let IterState { generator, range, offset } = <IterState<BlackRockGenerator> as serde::Deserialize>::deserialize(deserializer)?;
+        if !cursor::valid_positions(generator.range(), offset, range.start, range.end) {
+            return Err(serde::de::Error::custom(error::FromBytesError::InvalidPosition));
+        }
+
+        Ok(Self { range, generator, offset })
+    }
+}
+
+/// [`BlackRockIter`] over a `u128` range, see [`BlackRockGenerator128`].
+#[derive(Clone)]
+pub struct BlackRockIter128 {
+    range: Range<u128>,
+    generator: BlackRockGenerator128,
+}
+
+impl BlackRockIter128 {
+    /// Create a new `BlackRockIter128` with a specific range, seed, and rounds.
+    pub const fn with_seed_and_rounds(range: u128, seed: u64, rounds: usize) -> Self {
+        Self {
+            range: 0..range,
+            generator: BlackRockGenerator128::with_seed_and_rounds(range, seed, rounds),
+        }
+    }
+
+    /// Create a new `BlackRockIter128` with the provided seed and default rounds.
+    pub fn with_seed(range: u128, seed: u64) -> Self {
+        Self::with_seed_and_rounds(range, seed, 3)
+    }
+
+    #[cfg(feature = "rand")]
+    /// Create a new `BlackRockIter128` with a random seed and default rounds.
+    pub fn new(range: u128) -> Self {
+        Self::with_seed_and_rounds(range, rand::random(), 3)
+    }
+
+    /// The position of `value` in the full shuffled sequence, `None` if it is outside of the range.
+    pub fn position_of(&self, value: u128) -> Option<u128> {
+        (value < self.generator.range()).then(|| self.generator.unshuffle(value))
+    }
+}
+
+impl Iterator for BlackRockIter128 {
+    type Item = u128;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|x| self.generator.shuffle(x))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.range.nth(n).map(|x| self.generator.shuffle(x))
+    }
+}
+
+impl DoubleEndedIterator for BlackRockIter128 {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.range.next_back().map(|x| self.generator.shuffle(x))
     }
@@ -102,10 +780,12 @@ impl DoubleEndedIterator for BlackRockIter {
     }
 }
 
-impl FusedIterator for BlackRockIter {}
+impl FusedIterator for BlackRockIter128 {}
 
+#[derive(Clone)]
 pub struct BlackRockIpGenerator(BlackRockIter);
 
+#[cfg(feature = "rand")]
 impl Default for BlackRockIpGenerator {
     fn default() -> Self {
         Self::new()
@@ -113,13 +793,139 @@ impl Default for BlackRockIpGenerator {
 }
 
 impl BlackRockIpGenerator {
+    #[cfg(feature = "rand")]
     pub fn new() -> Self {
         Self(BlackRockIter::new(1 << 32))
     }
+
+    /// Scan the whole IPv4 space in the same order on every run, see [`BlackRockGenerator::deterministic`].
+    pub const fn deterministic() -> Self {
+        Self(BlackRockIter::deterministic(1 << 32))
+    }
+
+    #[cfg(feature = "rand")]
+    /// Scan the `/8` blocks starting with the octets in `order`, in that order, shuffling the hosts within each block,
+    /// with a random seed and default rounds. See [`TopOctetOrder::with_seed_and_rounds`](ip::TopOctetOrder::with_seed_and_rounds).
+    pub fn by_top_octet_order(order: &[u8]) -> ip::TopOctetOrder {
+        ip::TopOctetOrder::with_seed_and_rounds(order, rand::random(), 3)
+    }
+
+    #[cfg(feature = "rand")]
+    /// Scan every address outside of the `(network, prefix)` blocks in `excluded`, e.g. the reserved ranges,
+    /// with a random seed and default rounds. See [`ExcludingGenerator::with_seed_and_rounds`](ip::ExcludingGenerator::with_seed_and_rounds).
+    pub fn with_exclusions(excluded: &[(Ipv4Addr, u8)]) -> ip::ExcludingGenerator {
+        ip::ExcludingGenerator::with_seed_and_rounds(excluded, rand::random(), 3)
+    }
+
+    #[cfg(feature = "std")]
+    /// The expected number of distinct `/prefix` blocks touched by the first `k` addresses,
+    /// using the balls-in-bins expectation `B * (1 - (1 - 1/B)^k)` with `B = 2^prefix` blocks.
+    ///
+    /// That formula assumes addresses are drawn with replacement, the scan never repeats an address,
+    /// so the real count is very slightly higher once `k` gets close to the size of the space.
+    ///
+    /// # Panics
+    /// if `prefix` is larger than 32.
+    pub fn expected_distinct_prefixes(&self, k: u64, prefix: u8) -> f64 {
+        assert!(prefix <= 32, "invalid IPv4 prefix length {prefix}");
+
+        if k == 0 {
+            return 0.0;
+        }
+
+        let blocks = (1u64 << prefix) as f64;
+        let k = k.min(1 << 32) as f64;
+        blocks * -(k * (-1.0 / blocks).ln_1p()).exp_m1()
+    }
+
+    /// Group addresses that share a `/prefix` network into batches, e.g. to keep ARP tables warm.
+    ///
+    /// The scan order is kept, except that each batch also pulls in every address of its network
+    /// found in the next `max_lookahead` addresses. Every address still ends up in exactly one batch.
+    ///
+    /// # Panics
+    /// if `prefix` is larger than 32.
+    pub fn batch_by_prefix(self, prefix: u8, max_lookahead: usize) -> adapters::PrefixBatches<Self> {
+        assert!(prefix <= 32, "invalid IPv4 prefix length {prefix}");
+
+        adapters::PrefixBatches {
+            iter: self,
+            prefix,
+            lookahead: alloc::collections::VecDeque::with_capacity(max_lookahead),
+            max_lookahead,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    /// Yield addresses until at least `fraction` of all `/prefix` blocks in the range were touched,
+    /// the address that reaches the target is the last one yielded.
+    ///
+    /// Seen blocks are tracked in a set, so memory grows with the number of blocks touched,
+    /// up to 2<sup>prefix</sup> entries.
+    ///
+    /// # Panics
+    /// if `prefix` is larger than 32 or `fraction` is not in `[0, 1]`.
+    pub fn until_prefix_coverage(self, prefix: u8, fraction: f64) -> impl FusedIterator<Item = Ipv4Addr> {
+        assert!(prefix <= 32, "invalid IPv4 prefix length {prefix}");
+        assert!((0.0..=1.0).contains(&fraction), "coverage fraction must be in [0, 1], got {fraction}");
+
+        let blocks = self.0.generator.range().div_ceil(1 << (32 - prefix));
+        let target = (fraction * blocks as f64).ceil() as usize;
+
+        let mut seen = HashSet::new();
+        let mut done = target == 0;
+        self.take_while(move |&addr| {
+            if done {
+                return false;
+            }
+
+            seen.insert(adapters::network(addr, prefix));
+            done = seen.len() >= target;
+            true
+        })
+    }
+
+    /// Format every address as 8 uppercase hex digits of its big endian `u32`, e.g. `0A000001` for `10.0.0.1`.
+    pub fn as_hex(self) -> impl DoubleEndedIterator<Item = String> + FusedIterator {
+        self.map(|addr| format!("{:08X}", addr.to_bits()))
+    }
+
+    /// The 0-based position of `addr` in the scan order, i.e. how many addresses are scanned before it.
+    /// The IPv4 typed [`BlackRockIter::position_of`], every address is in the range.
+    pub fn rank_of(&self, addr: Ipv4Addr) -> u64 {
+        self.0.generator.unshuffle(u64::from(addr.to_bits()))
+    }
+
+    /// Cut off everything scanned after `addr`, so that [`next_back`](DoubleEndedIterator::next_back) yields `addr`
+    /// and then walks the scan order back toward the start, e.g. to re-probe the most recently scanned targets.
+    ///
+    /// Addresses already taken from either end stay taken: if `addr` was consumed from the front the result is empty,
+    /// if it was consumed from the back nothing is cut off.
+    pub fn reverse_from(mut self, addr: Ipv4Addr) -> Self {
+        let end = self.rank_of(addr) + 1;
+        let range = &mut self.0.range;
+        range.end = end.clamp(range.start, range.end);
+        self
+    }
+
+    /// Every address of the `net/prefix` block paired with its position in the scan order,
+    /// in address order. Host bits of `net` are ignored.
+    ///
+    /// # Panics
+    /// if `prefix` is larger than 32.
+    pub fn positions_in_cidr(&self, net: Ipv4Addr, prefix: u8) -> Vec<(Ipv4Addr, u64)> {
+        assert!(prefix <= 32, "invalid IPv4 prefix length {prefix}");
+
+        let size = 1 << (32 - prefix);
+        let start = u64::from(net.to_bits()) & !(size - 1);
+        (start..start + size)
+            .map(|x| (to_ip(x), self.0.generator.unshuffle(x)))
+            .collect()
+    }
 }
 
 const fn to_ip(x: u64) -> Ipv4Addr {
-    debug_assert!(x < u32::MAX as u64);
+    debug_assert!(x <= u32::MAX as u64);
     Ipv4Addr::from_bits(x as u32)
 }
 
@@ -144,6 +950,20 @@ impl Iterator for BlackRockIpGenerator {
     fn nth(&mut self, n: usize) -> Option<Self::Item> {
         self.0.nth(n).map(to_ip)
     }
+
+    fn fold<B, G>(self, init: B, mut f: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        self.0.fold(init, |acc, x| f(acc, to_ip(x)))
+    }
+
+    fn for_each<G>(self, mut f: G)
+    where
+        G: FnMut(Self::Item),
+    {
+        self.0.for_each(|x| f(to_ip(x)));
+    }
 }
 
 impl DoubleEndedIterator for BlackRockIpGenerator {
@@ -154,6 +974,13 @@ impl DoubleEndedIterator for BlackRockIpGenerator {
     fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
         self.0.nth_back(n).map(to_ip)
     }
+
+    fn rfold<B, G>(self, init: B, mut f: G) -> B
+    where
+        G: FnMut(B, Self::Item) -> B,
+    {
+        self.0.rfold(init, |acc, x| f(acc, to_ip(x)))
+    }
 }
 
 impl FusedIterator for BlackRockIpGenerator {}
@@ -162,12 +989,16 @@ impl FusedIterator for BlackRockIpGenerator {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::builder::BlackRockBuilder;
+    use std::collections::HashSet;
 
+    #[cfg(feature = "rand")]
     #[test]
     fn empty_on_zero() {
         assert!(BlackRockIter::new(0).next().is_none());
     }
     
+    #[cfg(feature = "rand")]
     #[test]
     fn test_ranges() {
         for range in 0..100 {
@@ -179,4 +1010,695 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cidr_positions() {
+        let generator = BlackRockBuilder::new().ipv4().seed(1).build_ip();
+        let positions = generator.positions_in_cidr(Ipv4Addr::new(192, 168, 7, 99), 28);
+
+        assert_eq!(positions.len(), 16);
+        for (i, &(addr, pos)) in positions.iter().enumerate() {
+            assert_eq!(addr, Ipv4Addr::new(192, 168, 7, 96 + i as u8));
+            assert_eq!(generator.0.position_of(addr.to_bits().into()), Some(pos));
+            assert_eq!(generator.0.generator.shuffle(pos), u64::from(addr.to_bits()));
+        }
+
+        assert_eq!(generator.positions_in_cidr(Ipv4Addr::new(1, 2, 3, 4), 32).len(), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn distinct_prefixes() {
+        let generator = BlackRockBuilder::new().ipv4().seed(1).build_ip();
+        assert_eq!(generator.expected_distinct_prefixes(0, 24), 0.0);
+        assert_eq!(generator.expected_distinct_prefixes(10, 0), 1.0);
+
+        for (k, prefix) in [(10_000, 24), (1000, 8), (100, 4)] {
+            let mut seen = BlackRockBuilder::new().ipv4().seed(1).build_ip()
+                .take(k)
+                .map(|ip| ip.to_bits() >> (32 - prefix))
+                .collect::<Vec<_>>();
+            seen.sort_unstable();
+            seen.dedup();
+
+            let expected = generator.expected_distinct_prefixes(k as u64, prefix);
+            let error = (seen.len() as f64 - expected).abs() / expected;
+            assert!(error < 0.01, "/{prefix}: {} vs {expected}", seen.len());
+        }
+    }
+
+    #[test]
+    fn at_positions() {
+        let all = BlackRockIter::with_seed(1000, 6).collect::<Vec<_>>();
+
+        let picked = BlackRockIter::with_seed(1000, 6).at_positions(|pos| pos % 7 == 3).collect::<Vec<_>>();
+        assert!(picked.iter().copied().eq(all.iter().copied().skip(3).step_by(7)));
+
+        let mut iter = BlackRockIter::with_seed(1000, 6);
+        iter.nth(499);
+        let back = iter.at_positions(|pos| pos % 100 == 0).rev().collect::<Vec<_>>();
+        assert_eq!(back, [all[900], all[800], all[700], all[600], all[500]]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn skip_logged() {
+        let all = BlackRockIter::with_seed(1000, 12).collect::<Vec<_>>();
+
+        // a log of the first 300 values with some probes lost, plus a value from outside the range
+        let mut probed = all[..300].iter().copied().filter(|x| x % 5 != 0).collect::<HashSet<_>>();
+        probed.insert(5000);
+
+        let mut iter = BlackRockIter::with_seed(1000, 12);
+        iter.nth(99);
+        let resumed = iter.skip_logged(&probed).collect::<Vec<_>>();
+
+        assert!(resumed.iter().all(|x| !probed.contains(x)));
+        let expected = all[100..].iter().copied().filter(|x| !probed.contains(x));
+        assert!(resumed.iter().copied().eq(expected));
+
+        let empty = HashSet::new();
+        assert!(BlackRockIter::with_seed(1000, 12).skip_logged(&empty).eq(all));
+    }
+
+    #[test]
+    fn padded_strings() {
+        let strings = BlackRockIter::with_seed(1000, 3).as_padded_strings(5).collect::<Vec<_>>();
+        assert!(strings.iter().all(|s| s.len() == 5 && s.starts_with("00")));
+
+        let values = strings.iter().map(|s| s.parse::<u64>().unwrap());
+        assert!(values.eq(BlackRockIter::with_seed(1000, 3)));
+
+        let mut narrow = BlackRockIter::with_seed(1000, 3).as_padded_strings(2).collect::<Vec<_>>();
+        narrow.sort_unstable_by_key(|s| s.parse::<u64>().unwrap());
+        assert_eq!(narrow[..3], ["00", "01", "02"]);
+        assert_eq!(narrow[999], "999");
+    }
+
+    #[test]
+    fn prefix_batches() {
+        let generator = || BlackRockBuilder::new().range(1 << 16).seed(8).build_ip();
+        let batches = generator().batch_by_prefix(24, 64).collect::<Vec<_>>();
+        assert!(batches.iter().all(|batch| batch.iter().all(|ip| ip.octets()[..3] == batch[0].octets()[..3])));
+        assert!(batches.iter().any(|batch| batch.len() > 1));
+
+        let mut flat = batches.concat();
+        assert_eq!(flat.len(), 1 << 16);
+        flat.sort_unstable();
+        assert!(flat.into_iter().eq((0..1 << 16).map(Ipv4Addr::from_bits)));
+
+        // batches start in scan order
+        let mut emitted = HashSet::new();
+        let mut scan = generator();
+        for batch in &batches {
+            let first = scan.find(|ip| !emitted.contains(ip)).unwrap();
+            assert_eq!(batch[0], first);
+            emitted.extend(batch.iter().copied());
+        }
+    }
+
+    #[test]
+    fn payload_seed() {
+        let pairs = BlackRockIter::with_seed(1000, 9).with_payload_seed().collect::<Vec<_>>();
+        assert_eq!(pairs, BlackRockIter::with_seed(1000, 9).with_payload_seed().collect::<Vec<_>>());
+
+        let values = BlackRockIter::with_seed(1000, 9).collect::<Vec<_>>();
+        assert!(pairs.iter().map(|&(x, _)| x).eq(values));
+
+        let mut payloads = pairs.iter().map(|&(_, payload)| payload).collect::<Vec<_>>();
+        payloads.sort_unstable();
+        payloads.dedup();
+        assert_eq!(payloads.len(), 1000);
+
+        let other = BlackRockIter::with_seed(1000, 10).with_payload_seed().map(|(_, payload)| payload);
+        assert!(!pairs.iter().map(|&(_, payload)| payload).eq(other));
+    }
+
+    #[test]
+    fn cache_line_batches() {
+        for range in [0, 8, 1001] {
+            let batches = BlackRockIter::with_seed(range, 3).cache_line_batches().collect::<Vec<_>>();
+            assert_eq!(batches.len() as u64, range.div_ceil(8));
+            assert!(batches.iter().rev().skip(1).all(|&(_, len)| len == 8));
+
+            let flat = batches.iter().flat_map(|(batch, _)| batch).copied().collect::<Vec<_>>();
+            let padding = flat.len() as u64 - range;
+            assert!(flat[range as usize..].iter().all(|&x| x == u64::MAX));
+            assert!(padding < 8);
+            assert!(batches.iter().flat_map(|(batch, len)| &batch[..*len]).copied().eq(BlackRockIter::with_seed(range, 3)));
+        }
+
+        // `u64::MAX` is a real value here, only the length separates it from the padding
+        let batches = BlackRockIter::inclusive(u64::MAX - 9..=u64::MAX, 3, 3).cache_line_batches().collect::<Vec<_>>();
+        assert_eq!(batches.iter().map(|&(_, len)| len).collect::<Vec<_>>(), [8, 2]);
+        let mut values = batches.iter().flat_map(|(batch, len)| &batch[..*len]).copied().collect::<Vec<_>>();
+        values.sort_unstable();
+        assert!(values.into_iter().eq(u64::MAX - 9..=u64::MAX));
+    }
+
+    #[test]
+    fn same_permutation() {
+        let mut a = BlackRockIter::with_seed_and_rounds(1000, 1, 3);
+        let b = BlackRockIter::with_seed_and_rounds(1000, 1, 3);
+        assert!(a.same_permutation_as(&b));
+
+        a.nth(100);
+        assert!(a.same_permutation_as(&b));
+
+        assert!(!a.same_permutation_as(&BlackRockIter::with_seed_and_rounds(1000, 2, 3)));
+        assert!(!a.same_permutation_as(&BlackRockIter::with_seed_and_rounds(1001, 1, 3)));
+        assert!(!a.same_permutation_as(&BlackRockIter::with_seed_and_rounds(1000, 1, 4)));
+    }
+
+    #[test]
+    fn doubling_blocks() {
+        let blocks = BlackRockIter::with_seed(1000, 4).doubling_blocks().collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 10);
+        assert!(blocks[..9].iter().enumerate().all(|(i, block)| block.len() == 1 << i));
+        assert_eq!(blocks[9].len(), 1000 - 511);
+        assert!(blocks.concat().into_iter().eq(BlackRockIter::with_seed(1000, 4)));
+
+        assert_eq!(BlackRockIter::with_seed(0, 4).doubling_blocks().count(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn prefix_coverage() {
+        let generator = || BlackRockBuilder::new().range(1 << 16).seed(2).build_ip();
+        let blocks = |addrs: &[Ipv4Addr]| addrs.iter().map(|ip| ip.to_bits() >> 8).collect::<HashSet<_>>().len();
+
+        let addrs = generator().until_prefix_coverage(24, 0.9).collect::<Vec<_>>();
+        assert_eq!(blocks(&addrs), 231);
+        assert_eq!(blocks(&addrs[..addrs.len() - 1]), 230);
+        assert!(addrs.iter().copied().eq(generator().take(addrs.len())));
+
+        assert_eq!(generator().until_prefix_coverage(24, 0.0).count(), 0);
+        assert_eq!(blocks(&generator().until_prefix_coverage(24, 1.0).collect::<Vec<_>>()), 256);
+    }
+
+    #[test]
+    fn ttl() {
+        let pairs = BlackRockIter::with_seed(1000, 9).with_ttl(32..=64).collect::<Vec<_>>();
+        assert!(pairs.iter().map(|&(x, _)| x).eq(BlackRockIter::with_seed(1000, 9)));
+        assert!(pairs.iter().all(|&(_, ttl)| (32..=64).contains(&ttl)));
+        assert!(pairs.iter().any(|&(_, ttl)| ttl == 32) && pairs.iter().any(|&(_, ttl)| ttl == 64));
+
+        assert!(pairs.iter().copied().eq(BlackRockIter::with_seed(1000, 9).with_ttl(32..=64)));
+        assert!(pairs.iter().rev().copied().eq(BlackRockIter::with_seed(1000, 9).with_ttl(32..=64).rev()));
+
+        assert!(BlackRockIter::with_seed(10, 9).with_ttl(0..=255).count() == 10);
+        assert!(BlackRockIter::with_seed(10, 9).with_ttl(7..=7).all(|(_, ttl)| ttl == 7));
+    }
+
+    #[test]
+    fn route_by_value() {
+        let mut shards = vec![Vec::new(); 7];
+        BlackRockIter::with_seed(1000, 3).route_by_value(7, |x, shard| shards[shard as usize].push(x));
+
+        for (shard, values) in (0..).zip(&shards) {
+            assert!(values.iter().all(|&x| x * 7 / 1000 == shard));
+        }
+
+        let mut routed = shards.concat();
+        routed.sort_unstable();
+        assert_eq!(routed, (0..1000).collect::<Vec<_>>());
+
+        // value * shards doesn't overflow on huge ranges
+        let mut iter = BlackRockIter::with_seed(u64::MAX, 3);
+        iter.range = 0..100;
+        iter.route_by_value(u64::MAX, |x, shard| assert_eq!(shard, x));
+    }
+
+    #[test]
+    fn inclusive() {
+        let mut values = BlackRockIter::inclusive(100..=200, 5, 3).collect::<Vec<_>>();
+        assert_eq!(values.len(), 101);
+        assert!(values.contains(&100) && values.contains(&200));
+
+        let iter = BlackRockIter::inclusive(100..=200, 5, 3);
+        assert!(values.iter().all(|&x| iter.position_of(x).is_some_and(|pos| iter.value(pos) == x)));
+        assert_eq!(iter.position_of(99), None);
+        assert_eq!(iter.position_of(201), None);
+
+        values.sort_unstable();
+        assert_eq!(values, (100..=200).collect::<Vec<_>>());
+
+        assert_eq!(BlackRockIter::inclusive(7..=7, 5, 3).collect::<Vec<_>>(), [7]);
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = BlackRockIter::inclusive(8..=7, 5, 3);
+        assert_eq!(empty.count(), 0);
+
+        let top = BlackRockIter::inclusive(u64::MAX - 9..=u64::MAX, 5, 3);
+        assert!(top.rev().all(|x| x >= u64::MAX - 9));
+        assert_eq!(BlackRockIter::inclusive(1..=u64::MAX, 5, 3).range, 0..u64::MAX);
+    }
+
+    #[test]
+    #[should_panic = "too long to shuffle"]
+    fn inclusive_full_range() {
+        let _ = BlackRockIter::inclusive(0..=u64::MAX, 5, 3);
+    }
+
+    #[test]
+    fn over_complement() {
+        let excluded = [900..2000, 10..20, 15..30, 500..500, 0..1];
+        let values = BlackRockIter::over_complement(1000, &excluded, 2, 3).collect::<Vec<_>>();
+
+        let expected = (0..1000)
+            .filter(|x| !excluded.iter().any(|hole| hole.contains(x)))
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), expected.len());
+
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, expected);
+
+        let back = BlackRockIter::over_complement(1000, &excluded, 2, 3).rev().collect::<Vec<_>>();
+        assert!(back.into_iter().rev().eq(values));
+
+        assert_eq!(BlackRockIter::over_complement(100, &[0..60, 50..100], 2, 3).count(), 0);
+        assert!(BlackRockIter::over_complement(100, &[], 2, 3).eq(BlackRockIter::with_seed_and_rounds(100, 2, 3)));
+    }
+
+    #[test]
+    fn hex() {
+        let generator = || BlackRockBuilder::new().ipv4().seed(1).build_ip().take(1000);
+        let hex = BlackRockBuilder::new().ipv4().seed(1).build_ip().as_hex().take(1000).collect::<Vec<_>>();
+
+        assert!(hex.iter().all(|s| s.len() == 8 && !s.contains(|c: char| c.is_ascii_lowercase())));
+        assert!(hex.iter().map(|s| Ipv4Addr::from_bits(u32::from_str_radix(s, 16).unwrap())).eq(generator()));
+
+        let single = BlackRockBuilder::new().range(1).seed(1).build_ip();
+        assert_eq!(single.as_hex().collect::<Vec<_>>(), ["00000000"]);
+    }
+
+    #[test]
+    fn sequence() {
+        let mut iter = BlackRockIter::with_seed(1000, 4);
+        iter.nth(9);
+
+        let pairs = iter.with_sequence(500).collect::<Vec<_>>();
+        assert_eq!(pairs.len(), 990);
+        assert!(pairs.iter().enumerate().all(|(i, &(seq, _))| seq == 500 + i as u64));
+        assert!(pairs.iter().map(|&(_, x)| x).eq(BlackRockIter::with_seed(1000, 4).skip(10)));
+
+        let back = BlackRockIter::with_seed(1000, 4).with_sequence(500).rev().collect::<Vec<_>>();
+        assert_eq!(back[0], (1499, BlackRockIter::with_seed(1000, 4).next_back().unwrap()));
+
+        let wrapped = BlackRockIter::with_seed(3, 4).with_sequence(u64::MAX).map(|(seq, _)| seq).collect::<Vec<_>>();
+        assert_eq!(wrapped, [u64::MAX, 0, 1]);
+    }
+
+    #[test]
+    fn rank_of() {
+        let generator = BlackRockBuilder::new().ipv4().seed(1).build_ip();
+        let emitted = BlackRockBuilder::new().ipv4().seed(1).build_ip().take(1000).collect::<Vec<_>>();
+        assert!(emitted.iter().enumerate().all(|(step, &addr)| generator.rank_of(addr) == step as u64));
+
+        let last = BlackRockBuilder::new().ipv4().seed(1).build_ip().next_back().unwrap();
+        assert_eq!(generator.rank_of(last), u32::MAX as u64);
+    }
+
+    #[test]
+    fn fresh() {
+        let mut iter = BlackRockIter::with_seed(100, 1);
+        assert!(iter.is_fresh());
+        iter.next();
+        assert!(!iter.is_fresh());
+
+        let mut iter = BlackRockIter::inclusive(10..=20, 1, 3);
+        assert!(iter.is_fresh());
+        iter.next_back();
+        assert!(!iter.is_fresh());
+
+        assert!(BlackRockIter::with_seed(0, 1).is_fresh());
+    }
+
+    #[test]
+    fn src_port() {
+        let pairs = BlackRockIter::with_seed(1000, 9).with_src_port(40_000..=60_000).collect::<Vec<_>>();
+        assert!(pairs.iter().map(|&(x, _)| x).eq(BlackRockIter::with_seed(1000, 9)));
+        assert!(pairs.iter().all(|&(_, port)| (40_000..=60_000).contains(&port)));
+        assert!(pairs.iter().copied().eq(BlackRockIter::with_seed(1000, 9).with_src_port(40_000..=60_000)));
+
+        // the port follows the value, wherever it lands in the sequence
+        let ports = pairs.iter().copied().collect::<std::collections::HashMap<_, _>>();
+        let wider = BlackRockIter::with_seed(2000, 9).with_src_port(40_000..=60_000);
+        assert!(wider.filter(|&(x, _)| x < 1000).all(|(x, port)| ports[&x] == port));
+
+        assert!(BlackRockIter::with_seed(10, 9).with_src_port(0..=u16::MAX).count() == 10);
+        assert!(BlackRockIter::with_seed(10, 9).with_src_port(443..=443).all(|(_, port)| port == 443));
+    }
+
+    #[test]
+    fn chained_hash() {
+        let chain = || BlackRockIter::with_seed(1000, 9).with_chained_hash();
+        let (_, last) = chain().last().unwrap();
+        assert_eq!(last, 0x85756a96b5741507);
+        assert!(chain().map(|(x, _)| x).eq(BlackRockIter::with_seed(1000, 9)));
+
+        // replaying the recorded values through the chain gives the same hashes
+        let recorded = BlackRockIter::with_seed(1000, 9).collect::<Vec<_>>();
+        let replay = recorded.iter().scan(9, |h, &x| {
+            *h = generator::sip(*h, x, 9);
+            Some(*h)
+        });
+        assert!(chain().map(|(_, h)| h).eq(replay));
+
+        let mut tampered = recorded.clone();
+        tampered.swap(10, 11);
+        let tampered = tampered.iter().fold(9, |h, &x| generator::sip(h, x, 9));
+        assert_ne!(tampered, last);
+
+        assert_ne!(BlackRockIter::with_seed(1000, 10).with_chained_hash().last().unwrap().1, last);
+    }
+
+    #[test]
+    fn reverse_from() {
+        let generator = || BlackRockBuilder::new().ipv4().seed(1).build_ip();
+        let emitted = generator().take(1000).collect::<Vec<_>>();
+
+        let back = generator().reverse_from(emitted[500]).rev().take(100).collect::<Vec<_>>();
+        assert!(back.iter().eq(emitted[401..=500].iter().rev()));
+        assert_eq!(generator().reverse_from(emitted[0]).rev().collect::<Vec<_>>(), [emitted[0]]);
+
+        let mut consumed = generator();
+        consumed.nth(500);
+        assert_eq!(consumed.clone().reverse_from(emitted[500]).next_back(), None);
+        assert!(consumed.reverse_from(emitted[700]).rev().eq(emitted[501..=700].iter().rev().copied()));
+    }
+
+    #[test]
+    fn last_ipv4_address() {
+        assert_eq!(to_ip(u32::MAX as u64), Ipv4Addr::BROADCAST);
+
+        // the position of 255.255.255.255 is where the full scan emits it
+        let mut generator = BlackRockBuilder::new().ipv4().seed(1).build_ip();
+        let pos = generator.rank_of(Ipv4Addr::BROADCAST);
+        assert_eq!(generator.nth(pos as usize), Some(Ipv4Addr::BROADCAST));
+    }
+
+    #[test]
+    fn balanced_queues() {
+        let mut queues = vec![Vec::new(); 7];
+        for (i, (queue, x)) in BlackRockIter::with_seed(1000, 2).balanced_queues(7).enumerate() {
+            assert_eq!(queue, i % 7);
+            queues[queue].push(x);
+        }
+
+        assert!(queues.iter().all(|queue| queue.len().abs_diff(1000 / 7) <= 1));
+
+        let mut all = queues.concat();
+        all.sort_unstable();
+        assert_eq!(all, (0..1000).collect::<Vec<_>>());
+
+        let mut resumed = BlackRockIter::with_seed(1000, 2);
+        resumed.nth(9);
+        assert_eq!(resumed.balanced_queues(7).next().map(|(queue, _)| queue), Some(10 % 7));
+    }
+
+    #[test]
+    fn stratified_groups() {
+        let groups = BlackRockIter::with_seed(10_000, 2).stratified_groups(10).map(Iterator::collect).collect::<Vec<Vec<_>>>();
+        assert_eq!(groups.len(), 10);
+
+        for group in &groups {
+            assert_eq!(group.len(), 1000);
+
+            // every tenth of the range gets roughly a tenth of each group
+            let mut deciles = [0; 10];
+            group.iter().for_each(|&x| deciles[x as usize / 1000] += 1);
+            assert!(deciles.iter().all(|&count| (50..150).contains(&count)), "{deciles:?}");
+        }
+
+        let mut all = groups.concat();
+        all.sort_unstable();
+        assert_eq!(all, (0..10_000).collect::<Vec<_>>());
+
+        let full = BlackRockIter::with_seed(10_000, 2).collect::<Vec<_>>();
+        assert!(groups[3].iter().eq(full.iter().skip(3).step_by(10)));
+
+        let mut resumed = BlackRockIter::with_seed(10_000, 2);
+        resumed.nth(11);
+        let mut resumed = resumed.stratified_groups(10).collect::<Vec<_>>();
+        assert!(resumed.remove(3).eq(full.iter().copied().skip(13).step_by(10)));
+        assert!(resumed.remove(1).eq(full.iter().copied().skip(21).step_by(10)));
+
+        assert_eq!(BlackRockIter::with_seed(3, 2).stratified_groups(5).map(Iterator::count).collect::<Vec<_>>(), [1, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn from_range_bounds() {
+        let ports = BlackRockIter::from_range_bounds(0..=65535, 3, 3).unwrap().collect::<HashSet<_>>();
+        assert_eq!(ports.len(), 65536);
+        assert!(ports.iter().all(|&port| port <= 65535));
+
+        let mut window = BlackRockIter::from_range_bounds(100..200, 3, 3).unwrap().collect::<Vec<_>>();
+        assert!(BlackRockIter::from_range_bounds(100..200, 3, 3).unwrap().eq(BlackRockIter::with_range(100..200, 3, 3)));
+        window.sort_unstable();
+        assert_eq!(window, (100..200).collect::<Vec<_>>());
+
+        assert!(BlackRockIter::from_range_bounds(..1024, 3, 3).unwrap().eq(BlackRockIter::with_seed_and_rounds(1024, 3, 3)));
+        assert!(BlackRockIter::from_range_bounds(u64::MAX - 9..=u64::MAX, 3, 3).unwrap().all(|x| x >= u64::MAX - 9));
+        assert_eq!(BlackRockIter::from_range_bounds((Bound::Included(200), Bound::Excluded(100)), 3, 3).unwrap().count(), 0);
+
+        let excluded_max = (Bound::Excluded(u64::MAX), Bound::Included(u64::MAX));
+        assert_eq!(BlackRockIter::from_range_bounds(excluded_max, 3, 3).unwrap().count(), 0);
+
+        assert_eq!(BlackRockIter::from_range_bounds(100.., 3, 3).err(), Some(error::RangeBoundsError::Unbounded));
+        assert_eq!(BlackRockIter::from_range_bounds(..=u64::MAX, 3, 3).err(), Some(error::RangeBoundsError::TooLong));
+        assert!(BlackRockIter::from_range_bounds(1..=u64::MAX, 3, 3).is_ok());
+    }
+
+    #[test]
+    fn with_range() {
+        let mut ports = BlackRockIter::with_range(1024..65536, 3, 3).collect::<Vec<_>>();
+        assert_eq!(ports.len(), 65536 - 1024);
+        ports.sort_unstable();
+        assert!(ports.into_iter().eq(1024..65536));
+
+        let mut iter = BlackRockIter::with_range(1024..65536, 3, 3);
+        assert_eq!(iter.size_hint(), (65536 - 1024, Some(65536 - 1024)));
+        let skipped = iter.nth(99).unwrap();
+        let last = iter.next_back().unwrap();
+        assert_eq!(iter.size_hint(), (65536 - 1024 - 101, Some(65536 - 1024 - 101)));
+        assert!((1024..65536).contains(&skipped) && (1024..65536).contains(&last));
+
+        let full = BlackRockIter::with_range(1024..65536, 3, 3).collect::<Vec<_>>();
+        assert_eq!((full[99], full[full.len() - 1]), (skipped, last));
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = BlackRockIter::with_range(10..5, 3, 3);
+        assert_eq!(empty.count(), 0);
+    }
+
+    #[test]
+    fn slots() {
+        let pairs = BlackRockIter::with_seed(100, 2).with_slot(6).collect::<Vec<_>>();
+        assert!(pairs.iter().enumerate().all(|(i, &(_, slot))| slot == i as u64 % 6));
+        assert!(pairs.iter().map(|&(x, _)| x).eq(BlackRockIter::with_seed(100, 2)));
+        assert!(BlackRockIter::with_seed(100, 2).with_slot(1).all(|(_, slot)| slot == 0));
+    }
+
+    #[test]
+    fn clone_forks() {
+        let mut iter = BlackRockIter::with_seed(10_000, 7);
+        iter.by_ref().take(1234).for_each(drop);
+        iter.next_back();
+
+        let fork = iter.clone();
+        assert!(iter.eq(fork));
+
+        let mut ips = BlackRockBuilder::new().range(1 << 16).seed(1).build_ip();
+        ips.nth(100);
+        assert!(ips.clone().eq(ips));
+
+        // adapters fork with their buffered state
+        let mut lanes = BlackRockIter::with_seed(10_000, 7).lanes(4, |x| (x % 4) as usize);
+        lanes.nth(1000);
+        assert!(lanes.clone().eq(lanes));
+
+        let mut rotating = BlackRockIter::with_rotation(10_000, 7, 3, 100);
+        rotating.nth(1234);
+        assert!(rotating.clone().eq(rotating));
+
+        let mut batches = BlackRockBuilder::new().range(1 << 16).seed(1).build_ip().batch_by_prefix(24, 64);
+        batches.nth(10);
+        assert!(batches.clone().eq(batches));
+
+        let mut octets = ip::TopOctetOrder::with_seed_and_rounds(&[1, 2], 7, 3);
+        octets.nth(1 << 24);
+        assert!(octets.clone().take(1000).eq(octets.take(1000)));
+    }
+
+    #[test]
+    fn retry_budget() {
+        let pairs = BlackRockIter::with_seed(1000, 9).with_retry_budget(1..=5).collect::<Vec<_>>();
+        assert!(pairs.iter().map(|&(x, _)| x).eq(BlackRockIter::with_seed(1000, 9)));
+        assert!(pairs.iter().all(|&(_, budget)| (1..=5).contains(&budget)));
+        assert!(pairs.iter().copied().eq(BlackRockIter::with_seed(1000, 9).with_retry_budget(1..=5)));
+        assert!((1..=5).all(|budget| pairs.iter().any(|&(_, b)| b == budget)));
+
+        // budgets follow the target, not its position
+        let budgets = pairs.iter().copied().collect::<std::collections::HashMap<_, _>>();
+        assert!(BlackRockIter::with_seed(1000, 9).with_retry_budget(1..=5).rev().all(|(x, budget)| budgets[&x] == budget));
+
+        assert!(BlackRockIter::with_seed(10, 9).with_retry_budget(0..=u8::MAX).count() == 10);
+        assert!(BlackRockIter::with_seed(10, 9).with_retry_budget(3..=3).all(|(_, budget)| budget == 3));
+    }
+
+    #[test]
+    fn checkpoint_round_trip() {
+        let mut iter = BlackRockIter::inclusive(100..=5099, 7, 3);
+        iter.by_ref().take(2500).for_each(drop);
+        iter.nth_back(10);
+
+        let bytes = iter.checkpoint().to_bytes();
+        let resumed = cursor::Cursor::from_bytes(&bytes).unwrap().resume();
+        assert!(resumed.same_permutation_as(&iter));
+        assert!(resumed.eq(iter));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut iter = BlackRockIter::inclusive(100..=5099, 7, 3);
+        iter.by_ref().take(2500).for_each(drop);
+        iter.nth_back(10);
+
+        let json = serde_json::to_string(&iter).unwrap();
+        let resumed = serde_json::from_str::<BlackRockIter>(&json).unwrap();
+        assert!(resumed.same_permutation_as(&iter));
+        assert!(resumed.eq(iter));
+
+        let past_end = r#"{"generator":{"range":1000,"seed":7,"rounds":3},"range":{"start":0,"end":1001},"offset":0}"#;
+        let err = serde_json::from_str::<BlackRockIter>(past_end).err().unwrap();
+        assert!(err.to_string().contains("cursor position is outside of the range"));
+
+        let overflowing = r#"{"generator":{"range":1000,"seed":7,"rounds":3},"range":{"start":0,"end":10},"offset":18446744073709551615}"#;
+        assert!(serde_json::from_str::<BlackRockIter>(overflowing).is_err());
+    }
+
+    #[test]
+    fn fold() {
+        let mut iter = BlackRockIter::inclusive(100..=1099, 4, 3);
+        iter.nth(10);
+        iter.next_back();
+
+        // a `for` loop drives `next` one value at a time
+        let mut manual = Vec::new();
+        for x in iter.clone() {
+            manual.push(x);
+        }
+
+        let folded = iter.clone().fold(Vec::new(), |mut acc, x| {
+            acc.push(x);
+            acc
+        });
+        assert_eq!(folded, manual);
+
+        let mut each = Vec::new();
+        iter.clone().for_each(|x| each.push(x));
+        assert_eq!(each, manual);
+
+        let rfolded = iter.clone().rfold(Vec::new(), |mut acc, x| {
+            acc.push(x);
+            acc
+        });
+        assert!(rfolded.into_iter().eq(manual.iter().copied().rev()));
+
+        assert_eq!((&mut iter).fold(0u64, |acc, x| acc.wrapping_mul(31).wrapping_add(x)), manual.iter().fold(0u64, |acc, &x| acc.wrapping_mul(31).wrapping_add(x)));
+        assert_eq!(iter.next(), None);
+
+        let ips = BlackRockBuilder::new().range(1 << 12).seed(3).build_ip();
+        let mut each = Vec::new();
+        ips.clone().for_each(|ip| each.push(ip));
+        assert_eq!(each, ips.clone().collect::<Vec<_>>());
+        assert!(ips.clone().rfold(Vec::new(), |mut acc, ip| {
+            acc.push(ip);
+            acc
+        }).into_iter().eq(ips.rev()));
+    }
+
+    #[test]
+    fn preview() {
+        let mut iter = BlackRockIter::with_seed(1000, 2);
+        iter.nth(5);
+
+        let preview = iter.preview(10);
+        assert_eq!(preview, iter.clone().take(10).collect::<Vec<_>>());
+        assert_eq!(iter.preview(2000).len(), 994);
+
+        // the original is untouched
+        assert_eq!(iter.position(), 6);
+        assert!(iter.take(10).eq(preview));
+    }
+
+    #[test]
+    fn deterministic() {
+        let a = BlackRockIter::deterministic(100_000).collect::<Vec<_>>();
+        let b = BlackRockIter::deterministic(100_000).collect::<Vec<_>>();
+        assert_eq!(a, b);
+        assert!(a.iter().copied().eq(BlackRockIter::with_seed(100_000, generator::DETERMINISTIC_SEED)));
+
+        assert!(BlackRockIpGenerator::deterministic().take(1000).eq(BlackRockIpGenerator::deterministic().take(1000)));
+    }
+
+    #[test]
+    fn resume_from_position() {
+        let full = BlackRockIter::with_seed_and_rounds(1000, 8, 3).collect::<Vec<_>>();
+
+        for k in [0, 1, 377, 999, 1000] {
+            let mut iter = BlackRockIter::with_seed_and_rounds(1000, 8, 3);
+            let mut values = iter.by_ref().take(k).collect::<Vec<_>>();
+            assert_eq!(iter.position(), k as u64);
+
+            values.extend(BlackRockIter::resume_from(1000, 8, 3, iter.position()));
+            assert_eq!(values, full);
+        }
+
+        assert_eq!(BlackRockIter::resume_from(1000, 8, 3, 5000).next(), None);
+    }
+
+    #[test]
+    fn advance_by() {
+        let iter = BlackRockIter::with_seed_and_rounds(1000, 8, 3);
+        for k in [0, 1, 377, 999] {
+            let (mut advanced, mut nth) = (iter.clone(), iter.clone());
+            assert_eq!(advanced.advance_by(k as u64), Ok(()));
+            assert_eq!(advanced.next(), nth.nth(k));
+
+            let (mut advanced, mut nth) = (iter.clone(), iter.clone());
+            assert_eq!(advanced.advance_back_by(k as u64), Ok(()));
+            assert_eq!(advanced.next_back(), nth.nth_back(k));
+        }
+
+        let mut short = iter.clone();
+        assert_eq!(short.advance_by(1003), Err(NonZero::new(3).unwrap()));
+        assert_eq!(short.next(), None);
+
+        // skipped indices are never shuffled, so striding a huge range is instant
+        let mut huge = BlackRockIter::with_seed(u64::MAX, 8);
+        assert_eq!(huge.advance_by(u64::MAX - 1), Ok(()));
+        assert_eq!(huge.position(), u64::MAX - 1);
+        assert!(huge.next().is_some_and(|x| x < u64::MAX) && huge.next().is_none());
+    }
+
+    #[test]
+    fn sample_fraction() {
+        for (fraction, expected) in [(0.0, 0), (0.05, 500), (0.12345, 1235), (1.0, 10_000)] {
+            let sample = BlackRockIter::sample_fraction(10_000, fraction, 4, 3).collect::<HashSet<_>>();
+            assert_eq!(sample.len(), expected);
+            assert!(sample.iter().all(|&x| x < 10_000));
+        }
+
+        let sample = BlackRockIter::sample_fraction(10_000, 0.05, 4, 3);
+        assert!(sample.eq(BlackRockIter::with_seed_and_rounds(10_000, 4, 3).take(500)));
+    }
+
+    #[test]
+    #[should_panic = "sample fraction must be in [0, 1]"]
+    fn sample_fraction_out_of_bounds() {
+        let _ = BlackRockIter::sample_fraction(100, 1.5, 4, 3);
+    }
+}
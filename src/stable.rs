@@ -0,0 +1,71 @@
+//! A shuffle that stays stable when the range grows.
+
+use alloc::vec::Vec;
+use crate::generator::sip;
+
+/// Orders `0..range` by a keyed hash of each index instead of a Feistel network.
+///
+/// Every index has a fixed rank that doesn't depend on the range, so growing the range
+/// only slots the new indices in between the old ones, which keep their relative order.
+/// [`BlackRockGenerator`](crate::generator::BlackRockGenerator) reshuffles everything when the range changes,
+/// the price here is that the order has to be materialized and sorted, O(range log range).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use = "this generator does nothing unless driven"]
+pub struct StableRankGenerator {
+    range: u64,
+    seed: u64,
+}
+
+impl StableRankGenerator {
+    /// Create a new `StableRankGenerator` with the provided seed.
+    pub const fn with_seed(range: u64, seed: u64) -> Self {
+        Self { range, seed }
+    }
+
+    #[cfg(feature = "rand")]
+    /// Create a new `StableRankGenerator` with a random seed.
+    pub fn new(range: u64) -> Self {
+        Self::with_seed(range, rand::random())
+    }
+
+    /// The rank of `index`, lower ranks come first. Independent of the range.
+    #[inline]
+    pub const fn rank(&self, index: u64) -> u64 {
+        sip(0, index, self.seed)
+    }
+
+    /// Every index in `0..range`, sorted by rank (ties broken by index).
+    ///
+    /// # Panics
+    /// if the range doesn't fit in memory addressable by `usize`.
+    pub fn order(&self) -> Vec<u64> {
+        let len = usize::try_from(self.range).expect("range is too large to materialize");
+
+        let mut order = Vec::with_capacity(len);
+        order.extend(0..self.range);
+        order.sort_unstable_by_key(|&i| (self.rank(i), i));
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutation() {
+        let mut order = StableRankGenerator::with_seed(1000, 3).order();
+        assert_ne!(order, (0..1000).collect::<Vec<_>>());
+
+        order.sort_unstable();
+        assert_eq!(order, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn stable_under_extension() {
+        let old = StableRankGenerator::with_seed(1000, 3).order();
+        let new = StableRankGenerator::with_seed(1500, 3).order();
+
+        assert_eq!(new.into_iter().filter(|&i| i < 1000).collect::<Vec<_>>(), old);
+    }
+}
@@ -0,0 +1,262 @@
+//! Flow control over a [`BlackRockIter`] or [`BlackRockIpGenerator`] for scanners with a bounded number of probes in flight.
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+use core::net::Ipv4Addr;
+use crate::{BlackRockIpGenerator, BlackRockIter};
+
+/// Hands out targets while keeping at most `w` of them in flight.
+///
+/// [`fill_window`](Self::fill_window) pulls as many targets as there are free slots,
+/// [`ack`](Self::ack) frees slots again once probes complete.
+pub struct BlackRockWindow {
+    iter: BlackRockIter,
+    window: usize,
+    in_flight: usize,
+}
+
+impl BlackRockWindow {
+    /// Create a window of `w` slots over `iter`.
+    ///
+    /// # Panics
+    /// if `w` is zero.
+    pub fn new(iter: BlackRockIter, w: usize) -> Self {
+        assert_ne!(w, 0, "window needs at least one slot");
+        Self { iter, window: w, in_flight: 0 }
+    }
+
+    /// Pull the next targets into every free slot, returns fewer once the iterator runs dry
+    /// and nothing if the window is full.
+    pub fn fill_window(&mut self) -> Vec<u64> {
+        let batch = self.iter.by_ref().take(self.window - self.in_flight).collect::<Vec<_>>();
+        self.in_flight += batch.len();
+        batch
+    }
+
+    /// Release `n` slots.
+    ///
+    /// # Panics
+    /// if `n` is larger than the number of targets in flight.
+    pub fn ack(&mut self, n: usize) {
+        assert!(n <= self.in_flight, "acked {n} targets with only {} in flight", self.in_flight);
+        self.in_flight -= n;
+    }
+
+    /// The number of targets handed out and not acked yet.
+    pub const fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    /// Returns `true` once every target was handed out and acked.
+    pub fn is_done(&self) -> bool {
+        self.in_flight == 0 && self.iter.size_hint().1 == Some(0)
+    }
+}
+
+/// Hands out addresses while keeping at most `cap` of them in flight per `/prefix` block,
+/// e.g. at most 4 outstanding probes per `/24` for polite scanning.
+///
+/// [`pull`](Self::pull) holds back addresses whose block is full, [`ack`](Self::ack) frees a slot
+/// and releases the oldest held back address of that block. Held back addresses are buffered,
+/// so memory use grows with how often the scan order runs into full blocks.
+///
+/// Every pulled address has to be acked exactly once, the limiter remembers the addresses in flight
+/// and panics on an ack of anything else.
+pub struct BlackRockPrefixLimiter {
+    iter: BlackRockIpGenerator,
+    prefix: u8,
+    cap: usize,
+    in_flight: BTreeMap<u32, usize>,
+    held: BTreeMap<u32, VecDeque<Ipv4Addr>>,
+    ready: VecDeque<Ipv4Addr>,
+    pending: BTreeSet<Ipv4Addr>,
+}
+
+impl BlackRockPrefixLimiter {
+    /// Create a limiter over `iter` allowing `cap` addresses in flight per `/prefix` block.
+    ///
+    /// # Panics
+    /// if `prefix` is larger than 32 or `cap` is zero.
+    pub fn new(iter: BlackRockIpGenerator, prefix: u8, cap: usize) -> Self {
+        assert!(prefix <= 32, "invalid IPv4 prefix length {prefix}");
+        assert_ne!(cap, 0, "cap needs to allow at least one address in flight");
+
+        Self {
+            iter,
+            prefix,
+            cap,
+            in_flight: BTreeMap::new(),
+            held: BTreeMap::new(),
+            ready: VecDeque::new(),
+            pending: BTreeSet::new(),
+        }
+    }
+
+    fn block(&self, addr: Ipv4Addr) -> u32 {
+        addr.to_bits().checked_shr(32 - u32::from(self.prefix)).unwrap_or(0)
+    }
+
+    /// Hand out the next address whose block has a free slot, released addresses first.
+    ///
+    /// Returns `None` if every remaining address is held back for now, or once everything was handed out,
+    /// see [`is_done`](Self::is_done) to tell the two apart.
+    pub fn pull(&mut self) -> Option<Ipv4Addr> {
+        // a released address got the slot its ack freed, so it never exceeds the cap
+        let addr = match self.ready.pop_front() {
+            Some(addr) => addr,
+            None => loop {
+                let addr = self.iter.next()?;
+                let block = self.block(addr);
+                if self.in_flight.get(&block).copied().unwrap_or(0) < self.cap {
+                    break addr;
+                }
+                self.held.entry(block).or_default().push_back(addr);
+            },
+        };
+
+        *self.in_flight.entry(self.block(addr)).or_default() += 1;
+        self.pending.insert(addr);
+        Some(addr)
+    }
+
+    /// Mark the probe of `addr` as complete, freeing a slot in its block.
+    ///
+    /// # Panics
+    /// if `addr` isn't in flight, it was never pulled or is acked twice.
+    pub fn ack(&mut self, addr: Ipv4Addr) {
+        assert!(self.pending.remove(&addr), "acked {addr} which isn't in flight");
+
+        // every pending address holds a slot in its block
+        let block = self.block(addr);
+        let count = self.in_flight.get_mut(&block).unwrap();
+        *count -= 1;
+        if *count == 0 {
+            self.in_flight.remove(&block);
+        }
+
+        if let Some(held) = self.held.get_mut(&block) {
+            self.ready.extend(held.pop_front());
+            if held.is_empty() {
+                self.held.remove(&block);
+            }
+        }
+    }
+
+    /// The number of addresses handed out and not acked yet, across all blocks.
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns `true` once every address was handed out and acked.
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty() && self.ready.is_empty() && self.held.is_empty() && self.iter.size_hint().1 == Some(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::BlackRockBuilder;
+
+    #[test]
+    fn fill_and_ack() {
+        let mut window = BlackRockWindow::new(BlackRockIter::with_seed(1000, 2), 16);
+        let mut outstanding = std::collections::VecDeque::new();
+        let mut seen = Vec::new();
+
+        let mut step = 0;
+        while !window.is_done() {
+            let batch = window.fill_window();
+            seen.extend_from_slice(&batch);
+            outstanding.extend(batch);
+            assert!(window.in_flight() <= 16);
+            assert_eq!(window.in_flight(), outstanding.len());
+
+            // complete a varying number of probes every step
+            let done = (step % 5 + 1).min(outstanding.len());
+            outstanding.drain(..done);
+            window.ack(done);
+            step += 1;
+        }
+
+        assert!(seen.iter().copied().eq(BlackRockIter::with_seed(1000, 2)));
+        assert!(window.fill_window().is_empty());
+    }
+
+    #[test]
+    fn full_window() {
+        let mut window = BlackRockWindow::new(BlackRockIter::with_seed(100, 2), 8);
+        assert_eq!(window.fill_window().len(), 8);
+        assert!(window.fill_window().is_empty());
+
+        window.ack(3);
+        assert_eq!(window.fill_window().len(), 3);
+    }
+
+    #[test]
+    fn prefix_limiter() {
+        // 16 /24 blocks
+        let iter = BlackRockBuilder::new().range(1 << 12).seed(3).build_ip();
+        let mut limiter = BlackRockPrefixLimiter::new(iter, 24, 3);
+        let mut outstanding = VecDeque::new();
+        let mut seen = std::collections::HashSet::new();
+
+        let mut step = 0;
+        while !limiter.is_done() {
+            // pull a burst, then complete a varying number of probes
+            for _ in 0..40 {
+                let Some(addr) = limiter.pull() else { break };
+                assert!(seen.insert(addr), "{addr} handed out twice");
+                outstanding.push_back(addr);
+            }
+
+            for block in 0..16 {
+                let in_block = outstanding.iter().filter(|addr| addr.octets()[2] == block).count();
+                assert!(in_block <= 3, "{in_block} addresses in flight in block {block}");
+            }
+            assert_eq!(limiter.in_flight(), outstanding.len());
+
+            for _ in 0..(step % 7 + 1).min(outstanding.len()) {
+                limiter.ack(outstanding.pop_front().unwrap());
+            }
+            step += 1;
+        }
+
+        assert_eq!(seen.len(), 1 << 12);
+        assert_eq!(limiter.pull(), None);
+    }
+
+    #[test]
+    #[should_panic = "acked 0.0.1.0 which isn't in flight"]
+    fn prefix_limiter_over_ack() {
+        let iter = BlackRockBuilder::new().range(1 << 12).seed(3).build_ip();
+        BlackRockPrefixLimiter::new(iter, 24, 3).ack(Ipv4Addr::new(0, 0, 1, 0));
+    }
+
+    #[test]
+    #[should_panic = "which isn't in flight"]
+    fn prefix_limiter_double_ack() {
+        let iter = BlackRockBuilder::new().range(1 << 12).seed(3).build_ip();
+        let mut limiter = BlackRockPrefixLimiter::new(iter, 24, 3);
+        let addr = limiter.pull().unwrap();
+        limiter.ack(addr);
+        limiter.ack(addr);
+    }
+
+    #[test]
+    #[should_panic = "which isn't in flight"]
+    fn prefix_limiter_unknown_ack() {
+        let iter = BlackRockBuilder::new().range(1 << 12).seed(3).build_ip();
+        let mut limiter = BlackRockPrefixLimiter::new(iter, 24, 3);
+        let addr = limiter.pull().unwrap();
+
+        // its block has a slot taken, but by `addr`, not by a neighbour that was never pulled
+        limiter.ack(Ipv4Addr::from_bits(addr.to_bits() ^ 1));
+    }
+
+    #[test]
+    #[should_panic = "acked 1 targets with only 0 in flight"]
+    fn over_ack() {
+        BlackRockWindow::new(BlackRockIter::with_seed(100, 2), 8).ack(1);
+    }
+}
@@ -0,0 +1,56 @@
+//! Driving a `BlackRockIter` must never touch the heap.
+//! This lives in its own test binary since it swaps out the global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use blackrock2::BlackRockIter;
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations_in(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn iteration_never_allocates() {
+    assert_eq!(allocations_in(|| drop(std::hint::black_box(Vec::<u8>::with_capacity(1)))), 1);
+
+    // the random constructors go through `rand`'s thread local rng, which allocates on first use,
+    // so stick to the seeded ones here.
+    let allocations = allocations_in(|| {
+        let mut sum = 0u64;
+        for x in BlackRockIter::with_seed(100_000, 7) {
+            sum = sum.wrapping_add(x);
+        }
+
+        let mut iter = BlackRockIter::with_seed_and_rounds(10_000, 7, 4);
+        sum = sum.wrapping_add(iter.nth(100).unwrap());
+        sum = sum.wrapping_add(iter.next_back().unwrap());
+        sum = sum.wrapping_add(iter.nth_back(100).unwrap());
+        sum = sum.wrapping_add(iter.count() as u64);
+
+        std::hint::black_box(sum);
+    });
+
+    assert_eq!(allocations, 0);
+}